@@ -0,0 +1,280 @@
+//! Derive macros for `peripheral_traits::common::{ToBytes, FromBytes}`.
+//!
+//! Supports structs with named fields and no generics. Fields are encoded
+//! back to back in declaration order. The byte order passed by the caller
+//! applies unless the struct or a field carries
+//! `#[bytes(endian = "little")]` or `#[bytes(endian = "big")]`; a field
+//! attribute overrides the struct attribute.
+//!
+//! The macros are re-exported from `peripheral_traits::common` by the
+//! `derive` feature; depend on that rather than on this crate.
+
+use proc_macro::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+
+const COMMON: &str = "::peripheral_traits::common";
+
+/// Derives `ToBytes` for a struct whose fields all implement `ToBytes`.
+#[proc_macro_derive(ToBytes, attributes(bytes))]
+pub fn derive_to_bytes(input: TokenStream) -> TokenStream {
+    match parse_struct(input) {
+        Ok(s) => expand_to_bytes(&s),
+        Err(msg) => compile_error(&msg),
+    }
+}
+
+/// Derives `FromBytes` and `FixedLen` for a struct whose fields all
+/// implement `FromBytes` and `FixedLen`.
+#[proc_macro_derive(FromBytes, attributes(bytes))]
+pub fn derive_from_bytes(input: TokenStream) -> TokenStream {
+    match parse_struct(input) {
+        Ok(s) => expand_from_bytes(&s),
+        Err(msg) => compile_error(&msg),
+    }
+}
+
+struct Field {
+    name: String,
+    ty: String,
+    endian: Option<&'static str>,
+}
+
+struct Struct {
+    name: String,
+    endian: Option<&'static str>,
+    fields: Vec<Field>,
+}
+
+impl Struct {
+    /// Byte order expression for `field`.
+    fn endian(&self, field: &Field) -> String {
+        match field.endian.or(self.endian) {
+            Some(variant) => format!("{COMMON}::Endian::{variant}"),
+            None => "__endian".into(),
+        }
+    }
+}
+
+fn expand_to_bytes(s: &Struct) -> TokenStream {
+    let mut len = String::from("0");
+    let mut write = String::new();
+    for field in &s.fields {
+        len += &format!(" + {COMMON}::ToBytes::byte_len(&self.{})", field.name);
+        write += &format!(
+            "__at += {COMMON}::ToBytes::to_bytes(&self.{}, &mut __dest[__at..], {})?;\n",
+            field.name,
+            s.endian(field)
+        );
+    }
+
+    format!(
+        "impl {COMMON}::ToBytes for {name} {{
+            fn byte_len(&self) -> usize {{
+                {len}
+            }}
+
+            fn to_bytes(
+                &self,
+                __dest: &mut [u8],
+                __endian: {COMMON}::Endian,
+            ) -> ::core::result::Result<usize, {COMMON}::BytesError> {{
+                let _ = __endian;
+                if __dest.len() < {COMMON}::ToBytes::byte_len(self) {{
+                    return ::core::result::Result::Err({COMMON}::BytesError::BufferTooSmall);
+                }}
+                let mut __at = 0;
+                {write}
+                ::core::result::Result::Ok(__at)
+            }}
+        }}",
+        name = s.name,
+    )
+    .parse()
+    .unwrap()
+}
+
+fn expand_from_bytes(s: &Struct) -> TokenStream {
+    let mut len = String::from("0");
+    let mut read = String::new();
+    let mut names = String::new();
+    // Fields are read into numbered temporaries so that no field name can
+    // shadow the generated locals.
+    for (i, field) in s.fields.iter().enumerate() {
+        let fixed = format!("<{} as {COMMON}::FixedLen>::BYTE_LEN", field.ty);
+        len += &format!(" + {fixed}");
+        read += &format!(
+            "let __field_{i} = <{ty} as {COMMON}::FromBytes>::from_bytes(
+                &__bytes[__at..__at + {fixed}],
+                {endian},
+            )?;
+            __at += {fixed};\n",
+            ty = field.ty,
+            endian = s.endian(field),
+        );
+        names += &format!("{}: __field_{i}, ", field.name);
+    }
+
+    format!(
+        "impl {COMMON}::FixedLen for {name} {{
+            const BYTE_LEN: usize = {len};
+        }}
+
+        impl {COMMON}::FromBytes for {name} {{
+            fn from_bytes(
+                __bytes: &[u8],
+                __endian: {COMMON}::Endian,
+            ) -> ::core::result::Result<Self, {COMMON}::BytesError> {{
+                let _ = __endian;
+                if __bytes.len() != <Self as {COMMON}::FixedLen>::BYTE_LEN {{
+                    return ::core::result::Result::Err({COMMON}::BytesError::InvalidLength);
+                }}
+                let mut __at = 0;
+                {read}
+                let _ = __at;
+                ::core::result::Result::Ok(Self {{ {names} }})
+            }}
+        }}",
+        name = s.name,
+    )
+    .parse()
+    .unwrap()
+}
+
+fn parse_struct(input: TokenStream) -> Result<Struct, String> {
+    let mut tokens = input.into_iter().peekable();
+    let mut endian = None;
+
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(attr)) = tokens.next() {
+                    endian = endian.or(parse_attr(&attr)?);
+                }
+            }
+            Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    if g.delimiter() == Delimiter::Parenthesis {
+                        tokens.next();
+                    }
+                }
+            }
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => break,
+            Some(TokenTree::Ident(i)) if matches!(i.to_string().as_str(), "enum" | "union") => {
+                return Err("only structs can derive ToBytes and FromBytes".into())
+            }
+            Some(_) => {}
+            None => return Err("expected a struct".into()),
+        }
+    }
+
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected a struct name".into()),
+    };
+    let body = match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => g,
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            return Err("generic structs are not supported".into())
+        }
+        _ => return Err("only structs with named fields are supported".into()),
+    };
+
+    let mut fields = Vec::new();
+    for tokens in split_fields(body.stream()) {
+        fields.push(parse_field(tokens)?);
+    }
+    Ok(Struct {
+        name,
+        endian,
+        fields,
+    })
+}
+
+/// Splits a struct body at the commas between fields.
+fn split_fields(body: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut fields = Vec::new();
+    let mut current = Vec::new();
+    let mut angle_depth = 0usize;
+    let mut prev_dash = false;
+    for token in body {
+        if let TokenTree::Punct(p) = &token {
+            match p.as_char() {
+                '<' => angle_depth += 1,
+                // `->` in a function pointer type is not a closing bracket.
+                '>' if !prev_dash => angle_depth = angle_depth.saturating_sub(1),
+                ',' if angle_depth == 0 => {
+                    fields.push(core::mem::take(&mut current));
+                    prev_dash = false;
+                    continue;
+                }
+                _ => {}
+            }
+            prev_dash = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+        } else {
+            prev_dash = false;
+        }
+        current.push(token);
+    }
+    if !current.is_empty() {
+        fields.push(current);
+    }
+    fields
+}
+
+fn parse_field(tokens: Vec<TokenTree>) -> Result<Field, String> {
+    let mut tokens = tokens.into_iter().peekable();
+    let mut endian = None;
+
+    let name = loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(attr)) = tokens.next() {
+                    endian = endian.or(parse_attr(&attr)?);
+                }
+            }
+            Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    if g.delimiter() == Delimiter::Parenthesis {
+                        tokens.next();
+                    }
+                }
+            }
+            Some(TokenTree::Ident(i)) => break i.to_string(),
+            _ => return Err("expected a field name".into()),
+        }
+    };
+    match tokens.next() {
+        Some(TokenTree::Punct(p)) if p.as_char() == ':' => {}
+        _ => return Err(format!("expected `:` after field `{name}`")),
+    }
+    let ty: TokenStream = tokens.collect();
+    Ok(Field {
+        name,
+        ty: ty.to_string(),
+        endian,
+    })
+}
+
+/// Parses the contents of `#[...]`, returning the byte order of a
+/// `bytes(endian = "...")` attribute and ignoring other attributes.
+fn parse_attr(attr: &Group) -> Result<Option<&'static str>, String> {
+    let mut tokens = attr.stream().into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "bytes" => {}
+        _ => return Ok(None),
+    }
+    let args = match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g,
+        _ => return Err("expected `#[bytes(endian = \"little\" | \"big\")]`".into()),
+    };
+
+    let args: Vec<String> = args.stream().into_iter().map(|t| t.to_string()).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["endian", "=", "\"little\""] => Ok(Some("Little")),
+        ["endian", "=", "\"big\""] => Ok(Some("Big")),
+        _ => Err("expected `#[bytes(endian = \"little\" | \"big\")]`".into()),
+    }
+}
+
+fn compile_error(msg: &str) -> TokenStream {
+    format!("::core::compile_error!({msg:?});").parse().unwrap()
+}
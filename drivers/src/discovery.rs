@@ -0,0 +1,79 @@
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+/// How a device was detected on the bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// The device acknowledged a one-byte read.
+    ReadAck,
+    /// The device acknowledged a zero-length write.
+    WriteAck,
+}
+
+/// A device found while scanning a bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscoveredDevice {
+    /// 7-bit bus address
+    pub address: u8,
+    /// How the device responded
+    pub probe: ProbeResult,
+    /// JEDEC or vendor identifier, if an identify step recognized the device
+    pub id: Option<u32>,
+}
+
+/// Probe method used when scanning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeMethod {
+    /// Read one byte. Safe for most devices, but consumes a byte from FIFOs.
+    Read,
+    /// Zero-length write ("quick write"). Not supported by every controller,
+    /// and some write-only devices latch it as a command.
+    Write,
+}
+
+/// Addresses reserved by the I2C specification and skipped by the scan.
+pub fn is_reserved(address: u8) -> bool {
+    !(0x08..=0x77).contains(&address)
+}
+
+/// Scans the 7-bit I2C address space.
+///
+/// Every non-reserved address is probed with `method`. Responding devices are
+/// passed to `identify`, which may read identification registers and return a
+/// JEDEC/vendor id, and then stored in `found`.
+///
+/// Returns the number of devices found, which may exceed `found.len()`; the
+/// devices that did not fit are not stored.
+pub fn scan_i2c<I2C, F>(
+    i2c: &mut I2C,
+    method: ProbeMethod,
+    mut identify: F,
+    found: &mut [DiscoveredDevice],
+) -> usize
+where
+    I2C: I2c<SevenBitAddress>,
+    F: FnMut(&mut I2C, u8) -> Option<u32>,
+{
+    let mut count = 0;
+
+    for address in (0u8..=0x7F).filter(|a| !is_reserved(*a)) {
+        let probe = match method {
+            ProbeMethod::Read => {
+                let mut buf = [0];
+                i2c.read(address, &mut buf).map(|_| ProbeResult::ReadAck)
+            }
+            ProbeMethod::Write => i2c.write(address, &[]).map(|_| ProbeResult::WriteAck),
+        };
+        // Any error, NACK or otherwise, means nothing usable answered.
+        let Ok(probe) = probe else {
+            continue;
+        };
+
+        let id = identify(i2c, address);
+        if let Some(slot) = found.get_mut(count) {
+            *slot = DiscoveredDevice { address, probe, id };
+        }
+        count += 1;
+    }
+
+    count
+}
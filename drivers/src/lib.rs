@@ -1,5 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod discovery;
 pub mod smbus;
 pub mod spi_device_driver;
 
@@ -0,0 +1,419 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use peripheral_traits::budget::ResourceBudget;
+use peripheral_traits::digest::{self, Digest, DigestCompletion, ResumableDigest};
+
+/// Size of the saved state of a [`SimulatedDigest`]: hash and length.
+const STATE_SIZE: usize = 16;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Limits of a [`SimulatedDigestEngine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestEngineConfig {
+    /// Number of contexts the engine can hold at once.
+    pub max_contexts: usize,
+    /// Throughput in bytes per simulated microsecond, at least 1.
+    pub bytes_per_us: u64,
+    /// Largest input accepted by one `submit_update`, e.g. the DMA FIFO.
+    pub max_submit: usize,
+}
+
+impl Default for DigestEngineConfig {
+    fn default() -> Self {
+        Self {
+            max_contexts: 1,
+            bytes_per_us: 64,
+            max_submit: 4096,
+        }
+    }
+}
+
+/// Error returned by [`SimulatedDigest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestEngineError {
+    /// The engine is absorbing a submission.
+    Busy,
+    /// All engine contexts are held by other operations.
+    NoContext,
+    /// The output or state buffer is too small.
+    BufferTooSmall,
+    /// An imported state has the wrong length.
+    InvalidState,
+}
+
+impl digest::Error for DigestEngineError {
+    fn kind(&self) -> digest::ErrorKind {
+        match self {
+            DigestEngineError::Busy | DigestEngineError::NoContext => digest::ErrorKind::Busy,
+            DigestEngineError::BufferTooSmall => digest::ErrorKind::InvalidOutputSize,
+            DigestEngineError::InvalidState => digest::ErrorKind::InvalidInputLength,
+        }
+    }
+}
+
+struct Engine {
+    config: DigestEngineConfig,
+    now_us: u64,
+    busy_until_us: u64,
+    contexts_in_use: usize,
+    callback: Option<(fn(usize), usize)>,
+}
+
+impl Engine {
+    fn check_idle(&self) -> Result<(), DigestEngineError> {
+        if self.now_us < self.busy_until_us {
+            Err(DigestEngineError::Busy)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Time to absorb `len` bytes at the configured throughput.
+    fn duration_us(&self, len: usize) -> u64 {
+        (len as u64).div_ceil(self.config.bytes_per_us.max(1))
+    }
+}
+
+/// Hash engine shared by several [`SimulatedDigest`] operations, for
+/// testing arbitration layers before hardware is available.
+///
+/// The engine holds a limited number of contexts: an operation claims one
+/// with its first input and releases it on `finalize`, `reset` or drop,
+/// and claiming beyond [`DigestEngineConfig::max_contexts`] fails with
+/// `ErrorKind::Busy`. Input is absorbed at a bounded rate on a simulated
+/// clock. A blocking `update` advances the clock; a `submit_update` keeps
+/// the whole engine busy until [`advance_us`](Self::advance_us) has moved
+/// past it, and every operation meanwhile fails with `ErrorKind::Busy`.
+///
+/// The hash is 64-bit FNV-1a, which is not a cryptographic hash.
+#[derive(Clone)]
+pub struct SimulatedDigestEngine {
+    engine: Rc<RefCell<Engine>>,
+}
+
+impl SimulatedDigestEngine {
+    pub fn new(config: DigestEngineConfig) -> Self {
+        Self {
+            engine: Rc::new(RefCell::new(Engine {
+                config,
+                now_us: 0,
+                busy_until_us: 0,
+                contexts_in_use: 0,
+                callback: None,
+            })),
+        }
+    }
+
+    /// Creates an operation on this engine. It holds no context until its
+    /// first input.
+    pub fn digest(&self) -> SimulatedDigest {
+        SimulatedDigest {
+            engine: self.engine.clone(),
+            state: None,
+        }
+    }
+
+    /// Current simulated time in microseconds.
+    pub fn now_us(&self) -> u64 {
+        self.engine.borrow().now_us
+    }
+
+    /// Advances the simulated clock, running the completion callback if a
+    /// submission finishes.
+    pub fn advance_us(&mut self, us: u64) {
+        let mut engine = self.engine.borrow_mut();
+        let was_busy = engine.now_us < engine.busy_until_us;
+        engine.now_us += us;
+        let done = was_busy && engine.now_us >= engine.busy_until_us;
+        let callback = engine.callback;
+        // Release the borrow, the callback may use the engine.
+        drop(engine);
+        if let (true, Some((callback, context))) = (done, callback) {
+            callback(context);
+        }
+    }
+
+    /// Number of contexts currently claimed.
+    pub fn contexts_in_use(&self) -> usize {
+        self.engine.borrow().contexts_in_use
+    }
+}
+
+#[derive(Clone, Copy)]
+struct State {
+    hash: u64,
+    len: u64,
+}
+
+/// Digest operation on a [`SimulatedDigestEngine`].
+pub struct SimulatedDigest {
+    engine: Rc<RefCell<Engine>>,
+    state: Option<State>,
+}
+
+impl SimulatedDigest {
+    /// Claims an engine context for this operation if it has none yet.
+    fn claim(&mut self) -> Result<&mut State, DigestEngineError> {
+        if self.state.is_none() {
+            let mut engine = self.engine.borrow_mut();
+            if engine.contexts_in_use >= engine.config.max_contexts {
+                return Err(DigestEngineError::NoContext);
+            }
+            engine.contexts_in_use += 1;
+        }
+        Ok(self.state.get_or_insert(State {
+            hash: FNV_OFFSET,
+            len: 0,
+        }))
+    }
+
+    fn release(&mut self) {
+        if self.state.take().is_some() {
+            self.engine.borrow_mut().contexts_in_use -= 1;
+        }
+    }
+
+    fn absorb(state: &mut State, input: &[u8]) {
+        for &byte in input {
+            state.hash = (state.hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+        state.len += input.len() as u64;
+    }
+}
+
+impl Drop for SimulatedDigest {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl digest::ErrorType for SimulatedDigest {
+    type Error = DigestEngineError;
+}
+
+impl ResourceBudget for SimulatedDigest {
+    const MAX_CONTEXT_SIZE: usize = STATE_SIZE;
+}
+
+impl Digest for SimulatedDigest {
+    type InitParams = ();
+
+    const OUTPUT_SIZE: usize = 8;
+
+    const BLOCK_SIZE: usize = 8;
+
+    fn init(_init_params: Self::InitParams) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+        self.engine.borrow().check_idle()?;
+        let state = self.claim()?;
+        Self::absorb(state, input);
+        let mut engine = self.engine.borrow_mut();
+        let duration = engine.duration_us(input.len());
+        engine.now_us += duration;
+        engine.busy_until_us = engine.now_us;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        self.engine.borrow().check_idle()?;
+        self.release();
+        Ok(())
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.engine.borrow().check_idle()?;
+        let out = out
+            .get_mut(..Self::OUTPUT_SIZE)
+            .ok_or(DigestEngineError::BufferTooSmall)?;
+        let hash = self.claim()?.hash;
+        out.copy_from_slice(&hash.to_be_bytes());
+        self.release();
+        Ok(())
+    }
+}
+
+impl ResumableDigest for SimulatedDigest {
+    /// Saves the state and keeps the context claimed.
+    fn export_state(&self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.engine.borrow().check_idle()?;
+        let buf = buf
+            .get_mut(..STATE_SIZE)
+            .ok_or(DigestEngineError::BufferTooSmall)?;
+        let state = self.state.unwrap_or(State {
+            hash: FNV_OFFSET,
+            len: 0,
+        });
+        buf[..8].copy_from_slice(&state.hash.to_le_bytes());
+        buf[8..].copy_from_slice(&state.len.to_le_bytes());
+        Ok(STATE_SIZE)
+    }
+
+    fn import_state(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.engine.borrow().check_idle()?;
+        let buf: &[u8; STATE_SIZE] = buf
+            .try_into()
+            .map_err(|_| DigestEngineError::InvalidState)?;
+        let state = self.claim()?;
+        state.hash = u64::from_le_bytes(buf[..8].try_into().unwrap());
+        state.len = u64::from_le_bytes(buf[8..].try_into().unwrap());
+        Ok(())
+    }
+}
+
+impl DigestCompletion for SimulatedDigest {
+    fn submit_update(&mut self, input: &[u8]) -> Result<usize, Self::Error> {
+        self.engine.borrow().check_idle()?;
+        let max_submit = self.engine.borrow().config.max_submit;
+        let accepted = &input[..input.len().min(max_submit)];
+        let state = self.claim()?;
+        Self::absorb(state, accepted);
+        let mut engine = self.engine.borrow_mut();
+        engine.busy_until_us = engine.now_us + engine.duration_us(accepted.len());
+        Ok(accepted.len())
+    }
+
+    fn poll_complete(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.engine.borrow().check_idle().is_ok())
+    }
+
+    fn set_completion_callback(&mut self, callback: Option<fn(usize)>, context: usize) {
+        self.engine.borrow_mut().callback = callback.map(|f| (f, context));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral_traits::digest::Error as _;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn engine(max_contexts: usize) -> SimulatedDigestEngine {
+        SimulatedDigestEngine::new(DigestEngineConfig {
+            max_contexts,
+            bytes_per_us: 64,
+            max_submit: 256,
+        })
+    }
+
+    fn finalize(digest: &mut SimulatedDigest) -> [u8; 8] {
+        let mut out = [0u8; 8];
+        digest.finalize(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn computes_fnv1a_and_charges_time() {
+        let engine = engine(1);
+        let mut digest = engine.digest();
+        assert_eq!(
+            finalize(&mut digest),
+            0xcbf2_9ce4_8422_2325u64.to_be_bytes()
+        );
+
+        digest.update(&mut b"a".to_owned()).unwrap();
+        assert_eq!(
+            finalize(&mut digest),
+            0xaf63_dc4c_8601_ec8cu64.to_be_bytes()
+        );
+
+        digest.update(&mut [0; 130]).unwrap();
+        assert_eq!(engine.now_us(), 1 + 3);
+        assert_eq!(
+            digest.finalize(&mut [0; 7]),
+            Err(DigestEngineError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn contexts_are_limited_and_released() {
+        let engine = engine(1);
+        let mut first = engine.digest();
+        let mut second = engine.digest();
+        assert_eq!(engine.contexts_in_use(), 0);
+
+        first.update(&mut [1]).unwrap();
+        assert_eq!(engine.contexts_in_use(), 1);
+        let err = second.update(&mut [2]).unwrap_err();
+        assert_eq!(err, DigestEngineError::NoContext);
+        assert_eq!(err.kind(), digest::ErrorKind::Busy);
+
+        finalize(&mut first);
+        assert_eq!(engine.contexts_in_use(), 0);
+        second.update(&mut [2]).unwrap();
+        assert!(first.update(&mut [1]).is_err());
+
+        second.reset().unwrap();
+        first.update(&mut [1]).unwrap();
+        drop(first);
+        assert_eq!(engine.contexts_in_use(), 0);
+    }
+
+    static COMPLETED: AtomicUsize = AtomicUsize::new(0);
+
+    fn on_complete(context: usize) {
+        COMPLETED.store(context, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn submission_keeps_the_engine_busy_until_done() {
+        let mut engine = engine(2);
+        let mut submitted = engine.digest();
+        let mut other = engine.digest();
+        submitted.set_completion_callback(Some(on_complete), 7);
+
+        // Only max_submit bytes are accepted, taking 4 µs.
+        assert_eq!(submitted.submit_update(&[0; 300]), Ok(256));
+        assert_eq!(submitted.poll_complete(), Ok(false));
+        let err = other.update(&mut [1]).unwrap_err();
+        assert_eq!(err, DigestEngineError::Busy);
+        assert_eq!(err.kind(), digest::ErrorKind::Busy);
+        assert_eq!(
+            submitted.submit_update(&[0; 1]),
+            Err(DigestEngineError::Busy)
+        );
+        assert_eq!(
+            submitted.finalize(&mut [0; 8]),
+            Err(DigestEngineError::Busy)
+        );
+
+        engine.advance_us(3);
+        assert_eq!(submitted.poll_complete(), Ok(false));
+        assert_eq!(COMPLETED.load(Ordering::Relaxed), 0);
+        engine.advance_us(1);
+        assert_eq!(submitted.poll_complete(), Ok(true));
+        assert_eq!(COMPLETED.load(Ordering::Relaxed), 7);
+
+        other.update(&mut [0; 256]).unwrap();
+        assert_eq!(finalize(&mut submitted), finalize(&mut other));
+    }
+
+    #[test]
+    fn exported_state_resumes_on_another_operation() {
+        let engine = engine(2);
+        let mut whole = engine.digest();
+        whole.update(&mut b"hello world".to_owned()).unwrap();
+        let expected = finalize(&mut whole);
+
+        let mut first = engine.digest();
+        first.update(&mut b"hello ".to_owned()).unwrap();
+        let mut state = [0u8; STATE_SIZE];
+        assert_eq!(first.export_state(&mut state), Ok(STATE_SIZE));
+        assert_eq!(engine.contexts_in_use(), 1);
+        drop(first);
+
+        let mut resumed = engine.digest();
+        assert_eq!(
+            resumed.import_state(&state[..8]),
+            Err(DigestEngineError::InvalidState)
+        );
+        resumed.import_state(&state).unwrap();
+        resumed.update(&mut b"world".to_owned()).unwrap();
+        assert_eq!(finalize(&mut resumed), expected);
+    }
+}
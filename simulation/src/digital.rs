@@ -28,6 +28,12 @@ impl SimulatedOutputPin {
     }
 }
 
+impl Default for SimulatedOutputPin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl embedded_hal::digital::Error for SimulatedPinError {
     fn kind(&self) -> ErrorKind {
         self.kind
@@ -0,0 +1,420 @@
+use peripheral_traits::block_device::{self, BlockDevice, ReadOnlyBlockDevice};
+use peripheral_traits::budget::ResourceBudget;
+use peripheral_traits::units::ByteOffset;
+use peripheral_traits::{digest, mac};
+
+/// Which calls a [`FaultyWrapper`] fails, and how.
+///
+/// `K` is the error kind reported for injected faults, e.g.
+/// `digest::ErrorKind::Busy`.
+#[derive(Debug, Clone, Copy)]
+pub struct FaultConfig<K> {
+    /// Inject a fault on every `every_nth` call; 0 disables injection.
+    pub every_nth: u32,
+    /// Number of consecutive calls failing per fault, at least 1. Values
+    /// above 1 model a device that stays not ready for a while.
+    pub burst: u32,
+    /// Kind reported by injected errors.
+    pub kind: K,
+    /// Let a faulted program write the first half of its data before
+    /// failing, modelling power loss mid-write.
+    pub partial_writes: bool,
+}
+
+impl<K> FaultConfig<K> {
+    /// Fails every `every_nth` call with `kind`.
+    pub fn every(every_nth: u32, kind: K) -> Self {
+        Self {
+            every_nth,
+            burst: 1,
+            kind,
+            partial_writes: false,
+        }
+    }
+}
+
+/// Error returned by [`FaultyWrapper`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultyError<E, K> {
+    /// Error of the wrapped implementation.
+    Inner(E),
+    /// Injected fault.
+    Injected(K),
+}
+
+impl<E: digest::Error> digest::Error for FaultyError<E, digest::ErrorKind> {
+    fn kind(&self) -> digest::ErrorKind {
+        match self {
+            FaultyError::Inner(e) => e.kind(),
+            FaultyError::Injected(kind) => *kind,
+        }
+    }
+}
+
+impl<E: mac::Error> mac::Error for FaultyError<E, mac::ErrorKind> {
+    fn kind(&self) -> mac::ErrorKind {
+        match self {
+            FaultyError::Inner(e) => e.kind(),
+            FaultyError::Injected(kind) => *kind,
+        }
+    }
+}
+
+impl<E: block_device::Error> block_device::Error for FaultyError<E, block_device::ErrorKind> {
+    fn kind(&self) -> block_device::ErrorKind {
+        match self {
+            FaultyError::Inner(e) => e.kind(),
+            FaultyError::Injected(kind) => *kind,
+        }
+    }
+}
+
+/// Wraps a digest, MAC or block device implementation and injects failures
+/// into its calls, so drivers' error paths can be exercised systematically.
+///
+/// Every fallible method call counts towards [`FaultConfig::every_nth`].
+/// A faulted call does not reach the wrapped implementation, except for
+/// the first half of a partial write. Size queries and `Digest::init`
+/// carry no instance and are passed through.
+pub struct FaultyWrapper<T, K> {
+    inner: T,
+    config: FaultConfig<K>,
+    calls: u32,
+    burst_left: u32,
+    injected: u32,
+}
+
+impl<T, K: Copy> FaultyWrapper<T, K> {
+    pub fn new(inner: T, config: FaultConfig<K>) -> Self {
+        Self {
+            inner,
+            config,
+            calls: 0,
+            burst_left: 0,
+            injected: 0,
+        }
+    }
+
+    /// Number of faults injected so far.
+    pub fn injected(&self) -> u32 {
+        self.injected
+    }
+
+    /// Replaces the fault configuration and restarts call counting.
+    pub fn set_config(&mut self, config: FaultConfig<K>) {
+        self.config = config;
+        self.calls = 0;
+        self.burst_left = 0;
+    }
+
+    /// Releases the wrapped implementation.
+    pub fn release(self) -> T {
+        self.inner
+    }
+
+    /// Counts a call and returns the kind to inject, if it is faulted.
+    fn fault(&mut self) -> Option<K> {
+        if self.burst_left > 0 {
+            self.burst_left -= 1;
+        } else {
+            self.calls += 1;
+            if self.config.every_nth == 0 || !self.calls.is_multiple_of(self.config.every_nth) {
+                return None;
+            }
+            self.burst_left = self.config.burst.saturating_sub(1);
+        }
+        self.injected += 1;
+        Some(self.config.kind)
+    }
+
+    fn call<R, E>(
+        &mut self,
+        op: impl FnOnce(&mut T) -> Result<R, E>,
+    ) -> Result<R, FaultyError<E, K>> {
+        match self.fault() {
+            Some(kind) => Err(FaultyError::Injected(kind)),
+            None => op(&mut self.inner).map_err(FaultyError::Inner),
+        }
+    }
+}
+
+impl<T: ResourceBudget, K> ResourceBudget for FaultyWrapper<T, K> {
+    const MAX_CONTEXT_SIZE: usize = T::MAX_CONTEXT_SIZE;
+    const MAX_CONCURRENT_OPS: usize = T::MAX_CONCURRENT_OPS;
+    const SCRATCH_BYTES_REQUIRED: usize = T::SCRATCH_BYTES_REQUIRED;
+}
+
+impl<T: digest::ErrorType> digest::ErrorType for FaultyWrapper<T, digest::ErrorKind> {
+    type Error = FaultyError<T::Error, digest::ErrorKind>;
+}
+
+impl<T: digest::Digest> digest::Digest for FaultyWrapper<T, digest::ErrorKind> {
+    type InitParams = T::InitParams;
+
+    const OUTPUT_SIZE: usize = T::OUTPUT_SIZE;
+
+    const BLOCK_SIZE: usize = T::BLOCK_SIZE;
+
+    fn init(init_params: Self::InitParams) -> Result<(), Self::Error> {
+        T::init(init_params).map_err(FaultyError::Inner)
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+        self.call(|d| d.update(input))
+    }
+
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        self.call(|d| d.reset())
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.call(|d| d.finalize(out))
+    }
+}
+
+impl<T: mac::ErrorType> mac::ErrorType for FaultyWrapper<T, mac::ErrorKind> {
+    type Error = FaultyError<T::Error, mac::ErrorKind>;
+}
+
+impl<T: mac::Mac> mac::Mac for FaultyWrapper<T, mac::ErrorKind> {
+    type InitParams = T::InitParams;
+
+    const OUTPUT_SIZE: usize = T::OUTPUT_SIZE;
+
+    fn init(init_params: Self::InitParams) -> Result<(), Self::Error> {
+        T::init(init_params).map_err(FaultyError::Inner)
+    }
+
+    fn set_key(&mut self, key: &[u8]) -> Result<(), Self::Error> {
+        self.call(|m| m.set_key(key))
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+        self.call(|m| m.update(input))
+    }
+
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        self.call(|m| m.reset())
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.call(|m| m.finalize(out))
+    }
+
+    fn verify(&mut self, tag: &[u8]) -> Result<(), Self::Error> {
+        self.call(|m| m.verify(tag))
+    }
+}
+
+impl<T: block_device::ErrorType> block_device::ErrorType
+    for FaultyWrapper<T, block_device::ErrorKind>
+{
+    type Error = FaultyError<T::Error, block_device::ErrorKind>;
+}
+
+impl<T: ReadOnlyBlockDevice> ReadOnlyBlockDevice for FaultyWrapper<T, block_device::ErrorKind> {
+    fn read_size(&self) -> usize {
+        self.inner.read_size()
+    }
+
+    fn read(&mut self, block_addr: ByteOffset, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.call(|d| d.read(block_addr, data))
+    }
+
+    fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+}
+
+impl<T: BlockDevice> BlockDevice for FaultyWrapper<T, block_device::ErrorKind> {
+    fn erase_size(&self) -> usize {
+        self.inner.erase_size()
+    }
+
+    fn erase(&mut self, block_addr: ByteOffset, size_in_bytes: usize) -> Result<(), Self::Error> {
+        self.call(|d| d.erase(block_addr, size_in_bytes))
+    }
+
+    fn program_size(&self) -> usize {
+        self.inner.program_size()
+    }
+
+    fn program(&mut self, block_addr: ByteOffset, data: &[u8]) -> Result<(), Self::Error> {
+        let Some(kind) = self.fault() else {
+            return self
+                .inner
+                .program(block_addr, data)
+                .map_err(FaultyError::Inner);
+        };
+        if self.config.partial_writes {
+            // Keep the torn prefix aligned so the device accepts it.
+            let unit = self.inner.program_size().max(1);
+            let half = (data.len() / 2) / unit * unit;
+            if half > 0 {
+                self.inner
+                    .program(block_addr, &data[..half])
+                    .map_err(FaultyError::Inner)?;
+            }
+        }
+        Err(FaultyError::Injected(kind))
+    }
+
+    fn program_capabilities(&self) -> block_device::ProgramCapabilities {
+        self.inner.program_capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral_traits::block_device::Error as _;
+
+    #[derive(Debug, PartialEq)]
+    struct Misaligned;
+
+    impl block_device::Error for Misaligned {
+        fn kind(&self) -> block_device::ErrorKind {
+            block_device::ErrorKind::OutOfBounds
+        }
+    }
+
+    /// Erased RAM device programmed in `unit`-byte words.
+    struct Ram {
+        data: [u8; 32],
+        unit: usize,
+    }
+
+    impl Ram {
+        fn new(unit: usize) -> Self {
+            Self {
+                data: [0xFF; 32],
+                unit,
+            }
+        }
+    }
+
+    impl block_device::ErrorType for Ram {
+        type Error = Misaligned;
+    }
+
+    impl ReadOnlyBlockDevice for Ram {
+        fn read_size(&self) -> usize {
+            1
+        }
+
+        fn read(&mut self, block_addr: ByteOffset, data: &mut [u8]) -> Result<(), Misaligned> {
+            let addr = block_addr.get();
+            data.copy_from_slice(&self.data[addr..addr + data.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    impl BlockDevice for Ram {
+        fn erase_size(&self) -> usize {
+            self.data.len()
+        }
+
+        fn erase(&mut self, _block_addr: ByteOffset, _size: usize) -> Result<(), Misaligned> {
+            self.data.fill(0xFF);
+            Ok(())
+        }
+
+        fn program_size(&self) -> usize {
+            self.unit
+        }
+
+        fn program(&mut self, block_addr: ByteOffset, data: &[u8]) -> Result<(), Misaligned> {
+            let addr = block_addr.get();
+            if !addr.is_multiple_of(self.unit) || !data.len().is_multiple_of(self.unit) {
+                return Err(Misaligned);
+            }
+            self.data[addr..addr + data.len()].copy_from_slice(data);
+            Ok(())
+        }
+    }
+
+    type FaultyRam = FaultyWrapper<Ram, block_device::ErrorKind>;
+
+    const KIND: block_device::ErrorKind = block_device::ErrorKind::ProgramError;
+
+    /// Reads `n` times and records which calls were faulted.
+    fn pattern(device: &mut FaultyRam, n: usize) -> Vec<bool> {
+        let mut buf = [0u8; 1];
+        (0..n)
+            .map(|_| device.read(ByteOffset(0), &mut buf).is_err())
+            .collect()
+    }
+
+    #[test]
+    fn every_nth_call_fails() {
+        let mut device = FaultyWrapper::new(Ram::new(1), FaultConfig::every(3, KIND));
+        assert_eq!(
+            pattern(&mut device, 7),
+            [false, false, true, false, false, true, false]
+        );
+        assert_eq!(device.injected(), 2);
+
+        let mut buf = [0u8; 1];
+        device.read(ByteOffset(0), &mut buf).unwrap();
+        let err = device.read(ByteOffset(0), &mut buf).unwrap_err();
+        assert_eq!(err, FaultyError::Injected(KIND));
+        assert_eq!(err.kind(), KIND);
+    }
+
+    #[test]
+    fn burst_fails_consecutive_calls_without_counting_them() {
+        let config = FaultConfig {
+            burst: 3,
+            ..FaultConfig::every(2, KIND)
+        };
+        let mut device = FaultyWrapper::new(Ram::new(1), config);
+        assert_eq!(
+            pattern(&mut device, 9),
+            [false, true, true, true, false, true, true, true, false]
+        );
+        assert_eq!(device.injected(), 6);
+    }
+
+    #[test]
+    fn zero_disables_injection_and_set_config_restarts_counting() {
+        let mut device = FaultyWrapper::new(Ram::new(1), FaultConfig::every(0, KIND));
+        assert_eq!(pattern(&mut device, 5), [false; 5]);
+
+        device.set_config(FaultConfig::every(2, KIND));
+        assert_eq!(pattern(&mut device, 4), [false, true, false, true]);
+        assert_eq!(device.injected(), 2);
+    }
+
+    #[test]
+    fn partial_write_keeps_an_aligned_prefix() {
+        let config = FaultConfig {
+            partial_writes: true,
+            ..FaultConfig::every(1, KIND)
+        };
+        let mut device = FaultyWrapper::new(Ram::new(4), config);
+        assert_eq!(
+            device.program(ByteOffset(0), &[0; 12]),
+            Err(FaultyError::Injected(KIND))
+        );
+        // Half of 12 bytes is 6, rounded down to the 4-byte program size.
+        let ram = device.release();
+        assert_eq!(ram.data[..4], [0; 4]);
+        assert!(ram.data[4..].iter().all(|&b| b == 0xFF));
+
+        // Less than one program unit leaves nothing written.
+        let mut device = FaultyWrapper::new(Ram::new(4), config);
+        assert!(device.program(ByteOffset(0), &[0; 4]).is_err());
+        assert!(device.release().data.iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn faulted_write_without_partial_writes_leaves_device_untouched() {
+        let mut device = FaultyWrapper::new(Ram::new(4), FaultConfig::every(1, KIND));
+        assert!(device.program(ByteOffset(0), &[0; 12]).is_err());
+        assert!(device.release().data.iter().all(|&b| b == 0xFF));
+    }
+}
@@ -1,8 +1,18 @@
 pub mod delay;
+pub mod digest_engine;
 pub mod digital;
+pub mod faulty;
+pub mod nor;
+pub mod rng;
 pub mod spi;
 
+pub use digest_engine::{
+    DigestEngineConfig, DigestEngineError, SimulatedDigest, SimulatedDigestEngine,
+};
 pub use digital::{SimulatedInputPin, SimulatedOutputPin};
+pub use faulty::{FaultConfig, FaultyError, FaultyWrapper};
+pub use nor::{NorError, NorTiming, SimulatedNorFlash};
+pub use rng::SimRng;
 pub use spi::SimulatedSpiBus;
 
 pub struct SimulatedPac {
@@ -15,10 +25,16 @@ pub struct SimulatedPac {
 impl SimulatedPac {
     pub fn new() -> Self {
         Self {
-            busy_pin: SimulatedInputPin::default(),
+            busy_pin: SimulatedInputPin,
             reset_pin: SimulatedOutputPin::new(),
             cs_pin: SimulatedOutputPin::new(),
             spi_master: SimulatedSpiBus::default(),
         }
     }
 }
+
+impl Default for SimulatedPac {
+    fn default() -> Self {
+        Self::new()
+    }
+}
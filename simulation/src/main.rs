@@ -11,7 +11,7 @@ pub fn main() {
         pac.cs_pin,
         pac.busy_pin,
         pac.reset_pin,
-        SimulatedDelay::default(),
+        SimulatedDelay,
     );
     driver.init().unwrap();
 
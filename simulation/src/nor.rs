@@ -0,0 +1,449 @@
+use peripheral_traits::block_device::{
+    self, BlockDevice, BlockDeviceProtection, ProgramCapabilities, ReadOnlyBlockDevice,
+};
+use peripheral_traits::capability::ProvisioningCap;
+use peripheral_traits::units::ByteOffset;
+
+/// Write-in-progress bit of the status register.
+pub const STATUS_WIP: u8 = 0x01;
+
+/// Write-enable-latch bit of the status register.
+pub const STATUS_WEL: u8 = 0x02;
+
+/// Operation latencies of a [`SimulatedNorFlash`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NorTiming {
+    /// Time to program one page, in microseconds.
+    pub page_program_us: u64,
+    /// Time to erase one sector, in microseconds.
+    pub sector_erase_us: u64,
+}
+
+impl Default for NorTiming {
+    /// Typical values of a serial NOR flash.
+    fn default() -> Self {
+        Self {
+            page_program_us: 700,
+            sector_erase_us: 45_000,
+        }
+    }
+}
+
+/// Error returned by [`SimulatedNorFlash`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NorError {
+    /// The range is outside the device.
+    OutOfBounds,
+    /// An erase is not aligned to sectors.
+    Misaligned,
+    /// The range overlaps a protected sector.
+    WriteProtected,
+    /// Power was cut during the operation; see [`SimulatedNorFlash::cut_power_at`].
+    PowerLoss,
+    /// The protection settings are locked down.
+    Locked,
+}
+
+impl block_device::Error for NorError {
+    fn kind(&self) -> block_device::ErrorKind {
+        match self {
+            NorError::OutOfBounds | NorError::Misaligned => block_device::ErrorKind::OutOfBounds,
+            NorError::WriteProtected | NorError::Locked => block_device::ErrorKind::WriteProtected,
+            NorError::PowerLoss => block_device::ErrorKind::ProgramError,
+        }
+    }
+}
+
+/// SPI NOR flash model with status register, timing and power loss.
+///
+/// Time is simulated: every program and erase sets the WIP bit for its
+/// latency, and the next operation first waits for it to clear by advancing
+/// the clock. Programming only clears bits, as on real NOR cells, and
+/// programs are split at page boundaries like the page program command.
+///
+/// [`cut_power_at`](Self::cut_power_at) schedules a power loss. An
+/// operation running at that moment is left partially applied, in
+/// proportion to the time it had run, and the device refuses further
+/// operations until [`power_on`](Self::power_on).
+pub struct SimulatedNorFlash {
+    data: Vec<u8>,
+    page_size: usize,
+    sector_size: usize,
+    timing: NorTiming,
+    now_us: u64,
+    busy_until_us: u64,
+    protected: Vec<bool>,
+    locked: bool,
+    power_cut_us: Option<u64>,
+    powered: bool,
+}
+
+impl SimulatedNorFlash {
+    /// Creates an erased device of `capacity` bytes.
+    ///
+    /// `capacity` must be a multiple of `sector_size`, which must be a
+    /// multiple of `page_size`.
+    pub fn new(capacity: usize, page_size: usize, sector_size: usize, timing: NorTiming) -> Self {
+        assert!(page_size > 0 && sector_size.is_multiple_of(page_size));
+        assert!(capacity.is_multiple_of(sector_size));
+        Self {
+            data: vec![0xFF; capacity],
+            page_size,
+            sector_size,
+            timing,
+            now_us: 0,
+            busy_until_us: 0,
+            protected: vec![false; capacity / sector_size],
+            locked: false,
+            power_cut_us: None,
+            powered: true,
+        }
+    }
+
+    /// Reads the status register at the current simulated time.
+    pub fn status(&self) -> u8 {
+        // The driver sets WEL before each program or erase and the device
+        // clears it when the operation completes.
+        if self.now_us < self.busy_until_us {
+            STATUS_WIP | STATUS_WEL
+        } else {
+            0
+        }
+    }
+
+    /// Current simulated time in microseconds.
+    pub fn now_us(&self) -> u64 {
+        self.now_us
+    }
+
+    /// Advances the simulated clock, e.g. while polling the status register.
+    pub fn advance_us(&mut self, us: u64) {
+        self.now_us += us;
+    }
+
+    /// Schedules a power loss at simulated time `at_us`.
+    pub fn cut_power_at(&mut self, at_us: u64) {
+        self.power_cut_us = Some(at_us);
+    }
+
+    /// Restores power after a power loss. Volatile state is reset.
+    pub fn power_on(&mut self) {
+        self.powered = true;
+        self.power_cut_us = None;
+        self.busy_until_us = self.now_us;
+    }
+
+    /// Raw contents, e.g. to inspect the result of a torn write.
+    pub fn contents(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn check_range(&self, addr: usize, len: usize) -> Result<(), NorError> {
+        match addr.checked_add(len) {
+            Some(end) if end <= self.data.len() => Ok(()),
+            _ => Err(NorError::OutOfBounds),
+        }
+    }
+
+    fn check_unprotected(&self, addr: usize, len: usize) -> Result<(), NorError> {
+        if len == 0 {
+            return Ok(());
+        }
+        let first = addr / self.sector_size;
+        let last = (addr + len - 1) / self.sector_size;
+        if self.protected[first..=last].iter().any(|&p| p) {
+            return Err(NorError::WriteProtected);
+        }
+        Ok(())
+    }
+
+    /// Waits for the previous operation and fails if power is already off.
+    fn ready(&mut self) -> Result<(), NorError> {
+        self.now_us = self.now_us.max(self.busy_until_us);
+        if self.power_cut_us.is_some_and(|cut| cut <= self.now_us) {
+            self.powered = false;
+        }
+        if self.powered {
+            Ok(())
+        } else {
+            Err(NorError::PowerLoss)
+        }
+    }
+
+    /// Starts an operation of `duration_us` on `len` bytes. On power loss
+    /// returns the number of bytes completed before the cut.
+    fn run(&mut self, duration_us: u64, len: usize) -> Result<usize, usize> {
+        let start = self.now_us;
+        let end = start + duration_us;
+        match self.power_cut_us {
+            Some(cut) if cut < end => {
+                self.now_us = cut;
+                self.powered = false;
+                let done = (cut - start) as u128 * len as u128 / duration_us.max(1) as u128;
+                Err(done as usize)
+            }
+            _ => {
+                self.busy_until_us = end;
+                Ok(len)
+            }
+        }
+    }
+}
+
+impl block_device::ErrorType for SimulatedNorFlash {
+    type Error = NorError;
+}
+
+impl ReadOnlyBlockDevice for SimulatedNorFlash {
+    fn read_size(&self) -> usize {
+        1
+    }
+
+    fn read(&mut self, block_addr: ByteOffset, data: &mut [u8]) -> Result<(), Self::Error> {
+        let addr = block_addr.get();
+        self.check_range(addr, data.len())?;
+        self.ready()?;
+        data.copy_from_slice(&self.data[addr..addr + data.len()]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl BlockDevice for SimulatedNorFlash {
+    fn erase_size(&self) -> usize {
+        self.sector_size
+    }
+
+    fn erase(&mut self, block_addr: ByteOffset, size_in_bytes: usize) -> Result<(), Self::Error> {
+        let addr = block_addr.get();
+        self.check_range(addr, size_in_bytes)?;
+        if !addr.is_multiple_of(self.sector_size) || !size_in_bytes.is_multiple_of(self.sector_size)
+        {
+            return Err(NorError::Misaligned);
+        }
+        self.check_unprotected(addr, size_in_bytes)?;
+
+        for sector in (addr..addr + size_in_bytes).step_by(self.sector_size) {
+            self.ready()?;
+            let (erased, result) = match self.run(self.timing.sector_erase_us, self.sector_size) {
+                Ok(n) => (n, Ok(())),
+                Err(partial) => (partial, Err(NorError::PowerLoss)),
+            };
+            self.data[sector..sector + erased].fill(0xFF);
+            result?;
+        }
+        Ok(())
+    }
+
+    fn program_size(&self) -> usize {
+        1
+    }
+
+    fn program(&mut self, block_addr: ByteOffset, data: &[u8]) -> Result<(), Self::Error> {
+        let addr = block_addr.get();
+        self.check_range(addr, data.len())?;
+        self.check_unprotected(addr, data.len())?;
+
+        let mut done = 0;
+        while done < data.len() {
+            let at = addr + done;
+            let n = (self.page_size - at % self.page_size).min(data.len() - done);
+            self.ready()?;
+            let (applied, result) = match self.run(self.timing.page_program_us, n) {
+                Ok(n) => (n, Ok(())),
+                Err(partial) => (partial, Err(NorError::PowerLoss)),
+            };
+            for (cell, byte) in self.data[at..at + applied].iter_mut().zip(&data[done..]) {
+                *cell &= byte;
+            }
+            result?;
+            done += n;
+        }
+        Ok(())
+    }
+
+    fn program_capabilities(&self) -> ProgramCapabilities {
+        ProgramCapabilities {
+            partial_page: true,
+            bit_clearing: true,
+            overwrite: false,
+        }
+    }
+}
+
+impl BlockDeviceProtection for SimulatedNorFlash {
+    fn write_protect(&mut self, addr: ByteOffset, len: usize) -> Result<(), Self::Error> {
+        self.set_protection(addr.get(), len, true)
+    }
+
+    fn write_unprotect(&mut self, addr: ByteOffset, len: usize) -> Result<(), Self::Error> {
+        self.set_protection(addr.get(), len, false)
+    }
+
+    fn is_write_protected(&self, addr: ByteOffset, len: usize) -> Result<bool, Self::Error> {
+        self.check_range(addr.get(), len)?;
+        Ok(self.check_unprotected(addr.get(), len).is_err())
+    }
+
+    fn lock_down(&mut self, _cap: &ProvisioningCap) -> Result<(), Self::Error> {
+        self.locked = true;
+        Ok(())
+    }
+
+    fn is_locked_down(&self) -> Result<bool, Self::Error> {
+        Ok(self.locked)
+    }
+}
+
+impl SimulatedNorFlash {
+    /// Protects or unprotects every sector overlapping the range.
+    fn set_protection(&mut self, addr: usize, len: usize, protect: bool) -> Result<(), NorError> {
+        self.check_range(addr, len)?;
+        if self.locked {
+            return Err(NorError::Locked);
+        }
+        if len > 0 {
+            let first = addr / self.sector_size;
+            let last = (addr + len - 1) / self.sector_size;
+            self.protected[first..=last].fill(protect);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMING: NorTiming = NorTiming {
+        page_program_us: 1000,
+        sector_erase_us: 4000,
+    };
+
+    /// Two 1 KiB sectors of 256-byte pages.
+    fn flash() -> SimulatedNorFlash {
+        SimulatedNorFlash::new(2048, 256, 1024, TIMING)
+    }
+
+    fn count(flash: &SimulatedNorFlash, byte: u8) -> usize {
+        flash.contents().iter().filter(|&&b| b == byte).count()
+    }
+
+    #[test]
+    fn program_clears_bits_and_sets_wip_for_its_latency() {
+        let mut flash = flash();
+        flash.program(ByteOffset(0), &[0x0F, 0xF0]).unwrap();
+        assert_eq!(flash.status(), STATUS_WIP | STATUS_WEL);
+        flash.advance_us(999);
+        assert_ne!(flash.status() & STATUS_WIP, 0);
+        flash.advance_us(1);
+        assert_eq!(flash.status(), 0);
+
+        flash.program(ByteOffset(0), &[0xFF, 0x3C]).unwrap();
+        let mut buf = [0u8; 2];
+        flash.read(ByteOffset(0), &mut buf).unwrap();
+        assert_eq!(buf, [0x0F, 0x30]);
+        // The read waited for the second program to finish.
+        assert_eq!(flash.now_us(), 2000);
+
+        flash.erase(ByteOffset(0), 1024).unwrap();
+        flash.read(ByteOffset(0), &mut buf).unwrap();
+        assert_eq!(buf, [0xFF; 2]);
+    }
+
+    #[test]
+    fn program_is_split_at_page_boundaries() {
+        let mut flash = flash();
+        flash.program(ByteOffset(128), &[0; 256]).unwrap();
+        flash.read(ByteOffset(0), &mut [0; 1]).unwrap();
+        assert_eq!(flash.now_us(), 2 * TIMING.page_program_us);
+    }
+
+    #[test]
+    fn power_cut_tears_program_in_proportion_to_elapsed_time() {
+        let mut flash = flash();
+        flash.cut_power_at(250);
+        assert_eq!(
+            flash.program(ByteOffset(0), &[0; 256]),
+            Err(NorError::PowerLoss)
+        );
+        assert_eq!(flash.now_us(), 250);
+        assert_eq!(flash.contents()[..64], [0; 64]);
+        assert_eq!(count(&flash, 0), 64);
+
+        assert_eq!(
+            flash.read(ByteOffset(0), &mut [0; 1]),
+            Err(NorError::PowerLoss)
+        );
+        flash.power_on();
+        assert_eq!(flash.status(), 0);
+        flash.read(ByteOffset(0), &mut [0; 1]).unwrap();
+    }
+
+    #[test]
+    fn power_cut_in_a_later_page_keeps_the_earlier_pages() {
+        let mut flash = flash();
+        flash.cut_power_at(1500);
+        assert_eq!(
+            flash.program(ByteOffset(0), &[0; 512]),
+            Err(NorError::PowerLoss)
+        );
+        assert_eq!(count(&flash, 0), 256 + 128);
+        assert_eq!(flash.contents()[..384], [0; 384]);
+    }
+
+    #[test]
+    fn power_cut_tears_erase_in_proportion_to_elapsed_time() {
+        let mut flash = flash();
+        flash.program(ByteOffset(0), &[0; 256]).unwrap();
+        // The erase starts once the program finishes at 1000 µs.
+        // An eighth of the 4000 µs erase covers 128 bytes of the sector.
+        flash.cut_power_at(1500);
+        assert_eq!(flash.erase(ByteOffset(0), 1024), Err(NorError::PowerLoss));
+        assert_eq!(flash.contents()[..128], [0xFF; 128]);
+        assert_eq!(flash.contents()[128..256], [0; 128]);
+    }
+
+    #[test]
+    fn cut_scheduled_between_operations_fails_the_next_one() {
+        let mut flash = flash();
+        flash.cut_power_at(0);
+        assert_eq!(
+            flash.program(ByteOffset(0), &[0; 4]),
+            Err(NorError::PowerLoss)
+        );
+        assert_eq!(count(&flash, 0), 0);
+    }
+
+    #[test]
+    fn protection_and_lock_down() {
+        let mut flash = flash();
+        flash.write_protect(ByteOffset(1024), 1).unwrap();
+        assert_eq!(flash.is_write_protected(ByteOffset(1500), 4), Ok(true));
+        assert_eq!(flash.is_write_protected(ByteOffset(0), 1024), Ok(false));
+        assert_eq!(
+            flash.program(ByteOffset(1020), &[0; 8]),
+            Err(NorError::WriteProtected)
+        );
+        assert_eq!(
+            flash.erase(ByteOffset(0), 2048),
+            Err(NorError::WriteProtected)
+        );
+        assert_eq!(flash.erase(ByteOffset(0), 512), Err(NorError::Misaligned));
+        assert_eq!(
+            flash.program(ByteOffset(2047), &[0; 2]),
+            Err(NorError::OutOfBounds)
+        );
+
+        let cap = ProvisioningCap::take().unwrap();
+        flash.lock_down(&cap).unwrap();
+        assert_eq!(flash.is_locked_down(), Ok(true));
+        assert_eq!(
+            flash.write_unprotect(ByteOffset(1024), 1),
+            Err(NorError::Locked)
+        );
+    }
+}
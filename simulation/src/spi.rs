@@ -68,9 +68,7 @@ impl<Word: Copy + 'static> SpiBus<Word> for SimulatedSpiBus {
     fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
         // Simulate simultaneous read and write
         let len = read.len().min(write.len());
-        for i in 0..len {
-            read[i] = write[i]; // Replace with actual transfer logic
-        }
+        read[..len].copy_from_slice(&write[..len]); // Replace with actual transfer logic
         Ok(())
     }
 
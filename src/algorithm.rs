@@ -0,0 +1,79 @@
+/// Namespaced algorithm identifier.
+///
+/// The identifier is a `u32` split into three fields so that vendors can
+/// allocate identifiers without colliding with standard algorithms or with
+/// each other:
+///
+/// | Bits   | Field                                           |
+/// |--------|-------------------------------------------------|
+/// | 31     | vendor flag, set for vendor-defined algorithms  |
+/// | 16..31 | namespace (vendor id when the flag is set)      |
+/// | 0..16  | algorithm number within the namespace           |
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AlgorithmId(u32);
+
+impl AlgorithmId {
+    const VENDOR_FLAG: u32 = 1 << 31;
+
+    /// Namespace of the standard algorithms below.
+    pub const STANDARD_NAMESPACE: u16 = 0;
+
+    pub const SHA1: Self = Self::standard(0x0001);
+    pub const SHA2_224: Self = Self::standard(0x0002);
+    pub const SHA2_256: Self = Self::standard(0x0003);
+    pub const SHA2_384: Self = Self::standard(0x0004);
+    pub const SHA2_512: Self = Self::standard(0x0005);
+    pub const SHA3_256: Self = Self::standard(0x0006);
+    pub const SHA3_384: Self = Self::standard(0x0007);
+    pub const SHA3_512: Self = Self::standard(0x0008);
+    pub const SM3: Self = Self::standard(0x0009);
+    pub const SM4: Self = Self::standard(0x0100);
+    pub const AES_128: Self = Self::standard(0x0101);
+    pub const AES_192: Self = Self::standard(0x0102);
+    pub const AES_256: Self = Self::standard(0x0103);
+    pub const HMAC_SHA2_256: Self = Self::standard(0x0200);
+    pub const HMAC_SHA2_384: Self = Self::standard(0x0201);
+    pub const HMAC_SHA2_512: Self = Self::standard(0x0202);
+    pub const AES_128_CMAC: Self = Self::standard(0x0203);
+    pub const AES_192_CMAC: Self = Self::standard(0x0204);
+    pub const AES_256_CMAC: Self = Self::standard(0x0205);
+    pub const KMAC128: Self = Self::standard(0x0206);
+    pub const KMAC256: Self = Self::standard(0x0207);
+
+    /// Identifier of a standard algorithm.
+    pub const fn standard(number: u16) -> Self {
+        Self(number as u32)
+    }
+
+    /// Identifier of a vendor-defined algorithm.
+    ///
+    /// `vendor` is truncated to 15 bits.
+    pub const fn vendor(vendor: u16, number: u16) -> Self {
+        Self(Self::VENDOR_FLAG | ((vendor as u32 & 0x7FFF) << 16) | number as u32)
+    }
+
+    /// Reconstructs an identifier from its raw representation.
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw representation.
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` for vendor-defined algorithms.
+    pub const fn is_vendor(self) -> bool {
+        self.0 & Self::VENDOR_FLAG != 0
+    }
+
+    /// Returns the namespace, i.e. the vendor id for vendor-defined algorithms.
+    pub const fn namespace(self) -> u16 {
+        ((self.0 >> 16) & 0x7FFF) as u16
+    }
+
+    /// Returns the algorithm number within its namespace.
+    pub const fn number(self) -> u16 {
+        self.0 as u16
+    }
+}
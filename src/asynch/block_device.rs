@@ -0,0 +1,34 @@
+pub use crate::block_device::{Error, ErrorKind, ErrorType};
+use crate::units::ByteOffset;
+
+/// Async variant of [`crate::block_device::BlockDevice`].
+///
+/// Operations complete once the device reports ready, so drivers on async
+/// SPI/QSPI hosts can yield instead of polling the status register.
+#[allow(async_fn_in_trait)]
+pub trait AsyncBlockDevice: ErrorType {
+    /// Size of a readable block in bytes.
+    fn read_size(&self) -> usize;
+
+    /// Reads `data.len()` bytes starting at `block_addr`.
+    async fn read(&mut self, block_addr: ByteOffset, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Size of an erasable block in bytes.
+    fn erase_size(&self) -> usize;
+
+    /// Erases `size_in_bytes` bytes starting at `block_addr`.
+    async fn erase(
+        &mut self,
+        block_addr: ByteOffset,
+        size_in_bytes: usize,
+    ) -> Result<(), Self::Error>;
+
+    /// Size of a programmable block in bytes.
+    fn program_size(&self) -> usize;
+
+    /// Programs `data` starting at `block_addr`.
+    async fn program(&mut self, block_addr: ByteOffset, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Size of the underlying device in bytes.
+    fn capacity(&self) -> usize;
+}
@@ -0,0 +1,37 @@
+pub use crate::digest::{Error, ErrorKind, ErrorType};
+
+/// Async variant of [`crate::digest::Digest`].
+#[allow(async_fn_in_trait)]
+pub trait AsyncDigest: ErrorType {
+    type InitParams;
+
+    /// Size of the hash output in bytes.
+    const OUTPUT_SIZE: usize;
+
+    /// Size of the hash input block in bytes, e.g. 64 for SHA-256.
+    const BLOCK_SIZE: usize;
+
+    /// Init instance of the crypto function with the given context.
+    ///
+    /// # Parameters
+    ///
+    /// - `init_params`: The context or configuration parameters for the crypto function.
+    async fn init(init_params: Self::InitParams) -> Result<(), Self::Error>;
+
+    /// Update state using provided input data, completing once the engine has absorbed it.
+    ///
+    /// # Parameters
+    ///
+    /// - `input`: The input data to be hashed.
+    async fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Reset instance to its initial state.
+    async fn reset(&mut self) -> Result<(), Self::Error>;
+
+    /// Finalize the computation and produce the output.
+    ///
+    /// # Parameters
+    ///
+    /// - `out`: A mutable slice to store the hash output. The length of the slice must be at least `OUTPUT_SIZE`.
+    async fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error>;
+}
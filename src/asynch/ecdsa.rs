@@ -0,0 +1,68 @@
+use crate::common::Zeroize;
+pub use crate::ecdsa::{EcdsaCurve, EcdsaTypes, Error, ErrorKind, ErrorType, HashMarker};
+
+/// Async variant of [`crate::ecdsa::EcdsaKeyGen`].
+#[allow(async_fn_in_trait)]
+pub trait AsyncEcdsaKeyGen: ErrorType + EcdsaTypes {
+    /// Generates an ECDSA key pair.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for key generation.
+    ///
+    /// # Returns
+    /// A result containing the generated private and public keys, or an error.
+    async fn generate_key_pair(
+        &mut self,
+        curve: &Self::Curve,
+    ) -> Result<(Self::PrivateKey, Self::PublicKey), Self::Error>;
+}
+
+/// Async variant of [`crate::ecdsa::EcdsaSign`].
+#[allow(async_fn_in_trait)]
+pub trait AsyncEcdsaSign: ErrorType {
+    type PrivateKey: Zeroize;
+    type Curve: EcdsaCurve;
+    type Signature;
+
+    /// Signs a message hash using the private key and elliptic curve.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for signing.
+    /// - `private_key`: The private key to use for signing.
+    /// - `message_hash`: The hash of the message to sign.
+    ///
+    /// # Returns
+    /// A result containing the generated signature, or an error.
+    async fn sign<H: HashMarker>(
+        &mut self,
+        curve: &Self::Curve,
+        private_key: &Self::PrivateKey,
+        message_hash: &[u8],
+    ) -> Result<Self::Signature, Self::Error>;
+}
+
+/// Async variant of [`crate::ecdsa::EcdsaVerify`].
+#[allow(async_fn_in_trait)]
+pub trait AsyncEcdsaVerify: ErrorType {
+    type PublicKey;
+    type Curve: EcdsaCurve;
+    type Signature;
+
+    /// Verifies an ECDSA signature.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for verification.
+    /// - `public_key`: The public key to use for verification.
+    /// - `message_hash`: The hash of the message to verify.
+    /// - `signature`: The signature to verify.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    async fn verify<H: HashMarker>(
+        &mut self,
+        curve: &Self::Curve,
+        public_key: &Self::PublicKey,
+        message_hash: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error>;
+}
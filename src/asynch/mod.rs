@@ -0,0 +1,9 @@
+//! Async variants of the peripheral traits.
+//!
+//! Mirrors the blocking traits for interrupt-driven engines used from async
+//! executors such as Embassy. Error types are shared with the blocking
+//! modules, so one error type can serve both variants of a driver.
+
+pub mod block_device;
+pub mod digest;
+pub mod ecdsa;
@@ -1,10 +1,38 @@
+use crate::budget::ResourceBudget;
+use crate::capability::ProvisioningCap;
+use crate::contract::ContractVersion;
+use crate::units::ByteOffset;
 
-
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ErrorKind {
     ReadError,
     ProgramError,
     EraseError,
     OutOfBounds,
+    /// The operation targets flash the CPU is executing from.
+    XipConflict,
+    /// The range is write-protected.
+    WriteProtected,
+    /// The block has reached its rated erase cycles.
+    WearLimitReached,
+    /// The operation targets a block marked bad.
+    BadBlock,
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ErrorKind::ReadError => "read failed",
+            ErrorKind::ProgramError => "program failed",
+            ErrorKind::EraseError => "erase failed",
+            ErrorKind::OutOfBounds => "address out of bounds",
+            ErrorKind::XipConflict => "range is executed from",
+            ErrorKind::WriteProtected => "range is write-protected",
+            ErrorKind::WearLimitReached => "wear limit reached",
+            ErrorKind::BadBlock => "bad block",
+        })
+    }
 }
 
 pub trait Error: core::fmt::Debug {
@@ -23,27 +51,249 @@ impl Error for core::convert::Infallible {
     }
 }
 
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
 /// A trait that BlockDevice implementations can use to share an error type.
 pub trait ErrorType {
 	/// Errors returned by this NOR flash.
 	type Error: Error;
 }
 
-/// Block devices are byte addressable but operate in units of "blocks".
-pub trait BlockDevice: ErrorType {
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+	/// Version of the trait contract this provider was built against.
+	fn contract_version(&self) -> ContractVersion {
+		TRAIT_CONTRACT_VERSION
+	}
+}
+
+/// Read access to a block device, e.g. a boot ROM or a write-protected flash.
+pub trait ReadOnlyBlockDevice: ErrorType {
 
     /// Get size of a reaadable block 
     fn  read_size(&self) -> usize;
-    fn read(&mut self, block_addr: usize, data : &mut[u8]) -> Result<(), Self::Error>; 
+    fn read(&mut self, block_addr: ByteOffset, data : &mut[u8]) -> Result<(), Self::Error>; 
+
+    /// Size of the underlying device in bytes
+    fn  capacity(&self) -> usize;
 
+    /// Reads consecutive bytes starting at `block_addr` into several buffers.
+    ///
+    /// The default implementation reads each buffer in turn; devices with
+    /// scatter-gather DMA can override it to issue a single transfer.
+    fn read_vectored(
+        &mut self,
+        block_addr: ByteOffset,
+        bufs: &mut [&mut [u8]],
+    ) -> Result<(), Self::Error> {
+        let mut addr = block_addr.get();
+        for buf in bufs.iter_mut() {
+            self.read(ByteOffset(addr), buf)?;
+            addr += buf.len();
+        }
+        Ok(())
+    }
+}
+
+/// What a device allows when programming, beyond programming erased blocks
+/// whole.
+///
+/// NOR flash typically allows all three; NAND flash allows none or only
+/// partial-page programs.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ProgramCapabilities {
+    /// Less than a full program page may be written per program operation.
+    pub partial_page: bool,
+    /// Already programmed bytes may be programmed again to clear further
+    /// bits (1 to 0 only), e.g. to update status flags in place.
+    pub bit_clearing: bool,
+    /// Bytes may be overwritten with arbitrary data without an erase.
+    pub overwrite: bool,
+}
+
+/// Block devices are byte addressable but operate in units of "blocks".
+pub trait BlockDevice: ReadOnlyBlockDevice {
 
     fn  erase_size(&self) -> usize; 
-    fn erase(&mut self, block_addr: usize, size_in_bytes: usize) -> Result<(), Self::Error>; 
+    fn erase(&mut self, block_addr: ByteOffset, size_in_bytes: usize) -> Result<(), Self::Error>; 
 
 
     fn  program_size(&self) -> usize; 
-    fn program(&mut self, block_addr: usize, data : &[u8]) -> Result<(), Self::Error>; 
+    fn program(&mut self, block_addr: ByteOffset, data : &[u8]) -> Result<(), Self::Error>; 
 
-    /// Size of the underlying device in bytes
-    fn  capacity(&self) -> usize;
-}
\ No newline at end of file
+    /// Programming semantics of the device.
+    ///
+    /// The default is the most restrictive set, so generic code that
+    /// honors it is correct on any device.
+    fn program_capabilities(&self) -> ProgramCapabilities {
+        ProgramCapabilities::default()
+    }
+
+    /// Programs several buffers to consecutive bytes starting at `block_addr`.
+    ///
+    /// Each buffer is programmed in turn by default, so every buffer but the
+    /// last should be a multiple of `program_size()`.
+    fn program_vectored(
+        &mut self,
+        block_addr: ByteOffset,
+        bufs: &[&[u8]],
+    ) -> Result<(), Self::Error> {
+        let mut addr = block_addr.get();
+        for buf in bufs {
+            self.program(ByteOffset(addr), buf)?;
+            addr += buf.len();
+        }
+        Ok(())
+    }
+}
+
+/// Sequential reads in caller-sized chunks, e.g. for streaming a firmware
+/// image through a digest without staging it in RAM.
+pub trait BlockStream: ErrorType {
+    /// Reads the next chunk into `buf`.
+    ///
+    /// # Returns
+    /// The number of bytes read, less than `buf.len()` only at the end of
+    /// the stream and 0 once it is exhausted.
+    fn read_next(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Offset of the next byte to be read.
+    fn position(&self) -> ByteOffset;
+}
+
+/// [`BlockStream`] over a range of a [`ReadOnlyBlockDevice`].
+pub struct StreamReader<D> {
+    device: D,
+    position: usize,
+    end: usize,
+}
+
+impl<D: ReadOnlyBlockDevice> StreamReader<D> {
+    /// Streams `len` bytes starting at `start`.
+    pub fn new(device: D, start: ByteOffset, len: usize) -> Self {
+        Self {
+            device,
+            position: start.get(),
+            end: start.get().saturating_add(len),
+        }
+    }
+
+    /// Releases the device.
+    pub fn release(self) -> D {
+        self.device
+    }
+}
+
+impl<D: ReadOnlyBlockDevice> ErrorType for StreamReader<D> {
+    type Error = D::Error;
+}
+
+impl<D: ResourceBudget> ResourceBudget for StreamReader<D> {
+    const MAX_CONTEXT_SIZE: usize = D::MAX_CONTEXT_SIZE;
+    const MAX_CONCURRENT_OPS: usize = D::MAX_CONCURRENT_OPS;
+    const SCRATCH_BYTES_REQUIRED: usize = D::SCRATCH_BYTES_REQUIRED;
+}
+
+impl<D: ReadOnlyBlockDevice> BlockStream for StreamReader<D> {
+    fn read_next(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let len = buf.len().min(self.end - self.position);
+        if len > 0 {
+            self.device.read(ByteOffset(self.position), &mut buf[..len])?;
+            self.position += len;
+        }
+        Ok(len)
+    }
+
+    fn position(&self) -> ByteOffset {
+        ByteOffset(self.position)
+    }
+}
+
+/// Write protection of block device ranges, e.g. SPI NOR block protect bits.
+///
+/// Lets generic code reject erases and programs of protected ranges before
+/// issuing them.
+pub trait BlockDeviceProtection: ErrorType {
+    /// Write-protects `len` bytes starting at `addr`.
+    ///
+    /// Devices protecting in coarser units may protect a larger range.
+    fn write_protect(&mut self, addr: ByteOffset, len: usize) -> Result<(), Self::Error>;
+
+    /// Removes write protection from `len` bytes starting at `addr`.
+    ///
+    /// Fails with `ErrorKind::WriteProtected` once protection is locked down.
+    fn write_unprotect(&mut self, addr: ByteOffset, len: usize) -> Result<(), Self::Error>;
+
+    /// Returns `true` if any byte of the range is write-protected.
+    fn is_write_protected(&self, addr: ByteOffset, len: usize) -> Result<bool, Self::Error>;
+
+    /// Freezes the current protection settings until the next power cycle,
+    /// or permanently on devices with one-time lock bits.
+    ///
+    /// # Parameters
+    /// - `cap`: Provisioning capability authorizing the lock-down.
+    fn lock_down(&mut self, cap: &ProvisioningCap) -> Result<(), Self::Error>;
+
+    /// Returns `true` if protection settings are locked down.
+    fn is_locked_down(&self) -> Result<bool, Self::Error>;
+}
+
+/// Wear statistics of a block device, for wear-leveling layers and
+/// predictive-maintenance telemetry.
+pub trait BlockDeviceHealth: ErrorType {
+    /// Returns the number of times the erase block at `block_addr` was erased.
+    fn erase_count(&self, block_addr: ByteOffset) -> Result<u32, Self::Error>;
+
+    /// Lists the erase blocks marked bad.
+    ///
+    /// # Parameters
+    /// - `out`: Receives the addresses of the bad blocks.
+    ///
+    /// # Returns
+    /// The total number of bad blocks, which may exceed `out.len()`; only
+    /// the first `out.len()` are written.
+    fn bad_blocks(&self, out: &mut [ByteOffset]) -> Result<usize, Self::Error>;
+
+    /// Estimated remaining life of the device in percent, from 100 when new
+    /// down to 0 once the rated erase cycles are used up.
+    fn remaining_life(&self) -> Result<u8, Self::Error>;
+}
+
+/// ECC a NAND device requires from its host.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct NandEccRequirement {
+    /// Bit errors that must be correctable per step; 0 if the device corrects
+    /// errors on-die.
+    pub correctable_bits: u8,
+    /// Bytes of main data covered by one ECC step.
+    pub step_size: usize,
+}
+
+/// Raw NAND flash.
+///
+/// Pages are `program_size()` bytes and erase blocks `erase_size()` bytes.
+/// Each page carries a spare (out-of-band) area, typically holding ECC and
+/// bad block marks, which the main-data methods do not touch.
+pub trait NandBlockDevice: BlockDevice {
+    /// Size of the spare area of one page in bytes.
+    fn spare_size(&self) -> usize;
+
+    /// Reads the spare area of the page at `page_addr`.
+    fn read_spare(&mut self, page_addr: ByteOffset, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Programs the spare area of the page at `page_addr`.
+    fn program_spare(&mut self, page_addr: ByteOffset, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Returns `true` if the erase block at `block_addr` is marked bad.
+    fn is_bad_block(&mut self, block_addr: ByteOffset) -> Result<bool, Self::Error>;
+
+    /// Marks the erase block at `block_addr` bad.
+    ///
+    /// Operations on a bad block then fail with `ErrorKind::BadBlock`.
+    fn mark_bad_block(&mut self, block_addr: ByteOffset) -> Result<(), Self::Error>;
+
+    /// Returns the ECC the host must apply to main data.
+    fn ecc_requirement(&self) -> NandEccRequirement;
+}
@@ -0,0 +1,69 @@
+//! Static RAM budgeting of providers.
+//!
+//! Providers declare their memory needs by implementing [`ResourceBudget`],
+//! so integrators can size arenas statically and reject configurations that
+//! do not fit.
+
+/// Memory needs of a provider.
+///
+/// Wrappers forward their inner provider's needs, adding their own.
+pub trait ResourceBudget {
+    /// Bytes of RAM holding the context of one in-flight operation.
+    const MAX_CONTEXT_SIZE: usize = 0;
+
+    /// Number of operations the provider can have in flight at once.
+    const MAX_CONCURRENT_OPS: usize = 1;
+
+    /// Bytes of scratch memory, e.g. DMA bounce buffers, needed besides
+    /// the per-operation contexts.
+    const SCRATCH_BYTES_REQUIRED: usize = 0;
+}
+
+/// RAM needed by a provider: one context per concurrent operation plus scratch.
+pub const fn footprint(
+    max_context_size: usize,
+    max_concurrent_ops: usize,
+    scratch_bytes_required: usize,
+) -> usize {
+    max_context_size * max_concurrent_ops + scratch_bytes_required
+}
+
+/// Fails compilation if the providers' combined [`footprint`] exceeds a budget.
+///
+/// ```
+/// use peripheral_traits::assert_ram_budget;
+/// use peripheral_traits::budget::ResourceBudget;
+///
+/// struct Sha;
+///
+/// impl ResourceBudget for Sha {
+///     const MAX_CONTEXT_SIZE: usize = 208;
+///     const MAX_CONCURRENT_OPS: usize = 4;
+/// }
+///
+/// assert_ram_budget!(1024; Sha);
+/// ```
+///
+/// ```compile_fail
+/// # use peripheral_traits::assert_ram_budget;
+/// # use peripheral_traits::budget::ResourceBudget;
+/// # struct Sha;
+/// # impl ResourceBudget for Sha {
+/// #     const MAX_CONTEXT_SIZE: usize = 208;
+/// #     const MAX_CONCURRENT_OPS: usize = 4;
+/// # }
+/// assert_ram_budget!(512; Sha);
+/// ```
+#[macro_export]
+macro_rules! assert_ram_budget {
+    ($budget:expr; $($provider:ty),+ $(,)?) => {
+        const _: () = assert!(
+            0 $(+ $crate::budget::footprint(
+                <$provider as $crate::budget::ResourceBudget>::MAX_CONTEXT_SIZE,
+                <$provider as $crate::budget::ResourceBudget>::MAX_CONCURRENT_OPS,
+                <$provider as $crate::budget::ResourceBudget>::SCRATCH_BYTES_REQUIRED,
+            ))+ <= $budget,
+            "providers exceed the RAM budget"
+        );
+    };
+}
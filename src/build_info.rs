@@ -0,0 +1,99 @@
+use crate::measurement::{MeasurementInfo, MeasurementType, Representation};
+
+/// Semantic version triple.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+/// Build metadata of the running firmware.
+///
+/// Lets field reports and attestation evidence name the exact build that
+/// produced them.
+pub trait BuildInfo {
+    /// Firmware version.
+    fn version(&self) -> Version;
+
+    /// Source revision, e.g. the 20-byte git commit hash.
+    fn source_revision(&self) -> &[u8];
+
+    /// Build time in seconds since the Unix epoch.
+    fn build_timestamp(&self) -> u64;
+
+    /// Names of the features enabled in this build.
+    fn features(&self) -> &[&str];
+}
+
+/// Serializes build metadata into a compact binary record.
+///
+/// Layout, all integers little endian:
+///
+/// | Field                   | Size         |
+/// |-------------------------|--------------|
+/// | major, minor, patch     | 3 x u16      |
+/// | build timestamp         | u64          |
+/// | revision length, bytes  | u8 + n       |
+/// | feature count           | u8           |
+/// | per feature: len, name  | u8 + n       |
+///
+/// # Returns
+///
+/// The number of bytes written, or `None` if `out` is too small or a field
+/// exceeds 255 bytes or entries.
+pub fn encode<B: BuildInfo + ?Sized>(info: &B, out: &mut [u8]) -> Option<usize> {
+    let mut w = Writer { out, pos: 0 };
+    let version = info.version();
+    w.put(&version.major.to_le_bytes())?;
+    w.put(&version.minor.to_le_bytes())?;
+    w.put(&version.patch.to_le_bytes())?;
+    w.put(&info.build_timestamp().to_le_bytes())?;
+    w.put_prefixed(info.source_revision())?;
+
+    let features = info.features();
+    w.put(&[u8::try_from(features.len()).ok()?])?;
+    for feature in features {
+        w.put_prefixed(feature.as_bytes())?;
+    }
+    Some(w.pos)
+}
+
+/// Writes the build metadata into `out` as an SPDM measurement block.
+///
+/// The measurement value is the [`encode`]d record, reported as a raw
+/// mutable firmware version so it can be included in attestation evidence.
+pub fn measurement<B: BuildInfo + ?Sized>(
+    info: &B,
+    index: u8,
+    out: &mut [u8],
+) -> Option<MeasurementInfo> {
+    let len = encode(info, out.get_mut(MeasurementInfo::HEADER_SIZE..)?)?;
+    let measurement = MeasurementInfo {
+        index,
+        measurement_type: MeasurementType::MutableFirmwareVersion,
+        representation: Representation::RawBitStream,
+        len,
+    };
+    measurement.encode_header(out)?;
+    Some(measurement)
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.pos.checked_add(bytes.len())?;
+        self.out.get_mut(self.pos..end)?.copy_from_slice(bytes);
+        self.pos = end;
+        Some(())
+    }
+
+    fn put_prefixed(&mut self, bytes: &[u8]) -> Option<()> {
+        self.put(&[u8::try_from(bytes.len()).ok()?])?;
+        self.put(bytes)
+    }
+}
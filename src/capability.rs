@@ -0,0 +1,28 @@
+//! Capability tokens for privileged operations.
+//!
+//! Destructive or irreversible trait methods take a reference to a
+//! zero-sized token as a parameter. Tokens cannot be constructed directly;
+//! the platform takes the single instance once at startup and hands it only
+//! to the code allowed to reach those methods. Tokens are neither `Copy`
+//! nor `Clone`.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+static PROVISIONING_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Grants access to irreversible provisioning operations: fuse
+/// programming, lock-down and lifecycle changes.
+#[derive(Debug)]
+pub struct ProvisioningCap {
+    _private: (),
+}
+
+impl ProvisioningCap {
+    /// Returns the token on the first call and `None` afterwards.
+    ///
+    /// Platform startup code calls this before running any other component,
+    /// so components that were not handed the token cannot obtain one.
+    pub fn take() -> Option<Self> {
+        (!PROVISIONING_TAKEN.swap(true, Ordering::AcqRel)).then_some(Self { _private: () })
+    }
+}
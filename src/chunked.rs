@@ -0,0 +1,664 @@
+use crate::block_device::BlockDevice;
+use crate::digest::DigestMigrate;
+use crate::journal::{self, Error as _, Journal, RecordId};
+use crate::range_check::{check_aligned, is_aligned};
+use crate::units::ByteOffset;
+
+/// Journal operation code of the record describing the image being programmed.
+pub const OP_IMAGE: u32 = 0x494D_4701;
+
+/// Journal operation code of a progress checkpoint.
+pub const OP_CHECKPOINT: u32 = 0x494D_4702;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error<DeviceError, DigestError, JournalError> {
+    /// Underlying block device error
+    Device(DeviceError),
+    /// Underlying digest engine error
+    Digest(DigestError),
+    /// Underlying journal error
+    Journal(JournalError),
+
+    /// Chunk size not a multiple of the erase and program sizes, or image out of bounds
+    InvalidGeometry,
+    /// Chunk larger than `CHUNK`, or short chunk before the end of the image
+    InvalidChunk,
+    /// More data supplied than the image length
+    Overrun,
+    /// Exported digest context larger than the checkpoint buffer
+    StateTooLarge,
+    /// Journal checkpoint unreadable
+    CorruptCheckpoint,
+}
+
+type ErrorOf<D, H, J> = Error<
+    <D as crate::block_device::ErrorType>::Error,
+    <H as crate::digest::ErrorType>::Error,
+    <J as crate::journal::ErrorType>::Error,
+>;
+
+/// Programs an image into a [`BlockDevice`] in fixed chunks, resumable after reset.
+///
+/// Each chunk of `CHUNK` bytes (a multiple of the erase size) is erased,
+/// programmed and fed into the running digest. After every chunk a checkpoint
+/// holding the committed offset and the exported digest context is appended
+/// to the journal, so an interrupted transfer can continue from the last
+/// committed chunk instead of starting over. `STATE` bounds the size of a
+/// checkpoint payload.
+///
+/// Each checkpoint completes the previous one, so only the image record and
+/// the latest checkpoint stay pending. When the journal runs full and holds no
+/// other pending records, it is cleared and both are rewritten; a reset in
+/// that window loses the progress and the image is programmed from the start.
+pub struct ChunkedProgrammer<D, H, J, const CHUNK: usize, const STATE: usize> {
+    device: D,
+    digest: H,
+    journal: J,
+    base: usize,
+    len: usize,
+    committed: usize,
+    image: RecordId,
+    checkpoint: Option<RecordId>,
+}
+
+impl<D, H, J, const CHUNK: usize, const STATE: usize> ChunkedProgrammer<D, H, J, CHUNK, STATE>
+where
+    D: BlockDevice,
+    H: DigestMigrate,
+    J: Journal,
+{
+    /// Starts programming a new image of `len` bytes at `base`.
+    ///
+    /// Records of an earlier, unfinished image are marked complete, so it can
+    /// no longer be resumed. The device must support partial-page programs.
+    ///
+    /// # Parameters
+    ///
+    /// - `digest`: A freshly initialized digest instance.
+    pub fn new(
+        device: D,
+        digest: H,
+        mut journal: J,
+        base: ByteOffset,
+        len: usize,
+    ) -> Result<Self, ErrorOf<D, H, J>> {
+        const { assert!(STATE >= 4, "checkpoint must hold the committed offset") };
+        let base = base.get();
+        Self::check_geometry(&device, base, len)?;
+
+        let mut from = 0;
+        while let Some(intent) = journal
+            .scan_incomplete(from, &mut [])
+            .map_err(Error::Journal)?
+        {
+            from = intent.id + 1;
+            if matches!(intent.op, OP_IMAGE | OP_CHECKPOINT) {
+                journal.mark_complete(intent.id).map_err(Error::Journal)?;
+            }
+        }
+
+        let payload = Self::image_payload(base, len);
+        let image = match journal.append_intent(OP_IMAGE, &payload) {
+            Err(e) if e.kind() == journal::ErrorKind::Full => {
+                if !Self::reclaim(&mut journal, &[]).map_err(Error::Journal)? {
+                    return Err(Error::Journal(e));
+                }
+                journal.append_intent(OP_IMAGE, &payload)
+            }
+            result => result,
+        }
+        .map_err(Error::Journal)?;
+
+        Ok(Self {
+            device,
+            digest,
+            journal,
+            base,
+            len,
+            committed: 0,
+            image,
+            checkpoint: None,
+        })
+    }
+
+    /// Resumes an image whose programming was interrupted.
+    ///
+    /// # Parameters
+    ///
+    /// - `digest`: A digest instance able to import the checkpointed context.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the journal holds no unfinished image. Otherwise the caller
+    /// continues feeding the image from [`Self::committed`].
+    pub fn resume(
+        device: D,
+        mut digest: H,
+        mut journal: J,
+    ) -> Result<Option<Self>, ErrorOf<D, H, J>> {
+        const { assert!(STATE >= 4, "checkpoint must hold the committed offset") };
+        let mut payload = [0u8; STATE];
+        let mut state = [0u8; STATE];
+        let mut image = None;
+        let mut checkpoint = None;
+        let mut from = 0;
+
+        while let Some(intent) = journal
+            .scan_incomplete(from, &mut payload)
+            .map_err(Error::Journal)?
+        {
+            from = intent.id + 1;
+            match intent.op {
+                OP_IMAGE if intent.len == 8 => {
+                    let base = u32::from_le_bytes(payload[..4].try_into().unwrap()) as usize;
+                    let len = u32::from_le_bytes(payload[4..8].try_into().unwrap()) as usize;
+                    image = Some((intent.id, base, len));
+                    checkpoint = None;
+                }
+                OP_CHECKPOINT if image.is_some() => {
+                    if intent.len > STATE || intent.len < 4 {
+                        return Err(Error::CorruptCheckpoint);
+                    }
+                    let committed = u32::from_le_bytes(payload[..4].try_into().unwrap()) as usize;
+                    checkpoint = Some((intent.id, committed, intent.len));
+                    state[..intent.len].copy_from_slice(&payload[..intent.len]);
+                }
+                _ => {}
+            }
+        }
+
+        let Some((image, base, len)) = image else {
+            return Ok(None);
+        };
+        Self::check_geometry(&device, base, len)?;
+        let committed = checkpoint.map_or(0, |(_, committed, _)| committed);
+        if committed > len {
+            return Err(Error::CorruptCheckpoint);
+        }
+        // Only the last checkpoint of the last image carries the context;
+        // earlier ones may belong to an abandoned image.
+        if let Some((_, _, state_len)) = checkpoint {
+            digest
+                .import_context(&state[4..state_len])
+                .map_err(Error::Digest)?;
+        }
+
+        Ok(Some(Self {
+            device,
+            digest,
+            journal,
+            base,
+            len,
+            committed,
+            image,
+            checkpoint: checkpoint.map(|(id, _, _)| id),
+        }))
+    }
+
+    /// Releases the device, digest and journal without completing the image.
+    ///
+    /// The image stays unfinished in the journal and can be resumed later.
+    pub fn release(self) -> (D, H, J) {
+        (self.device, self.digest, self.journal)
+    }
+
+    /// Number of image bytes programmed and checkpointed so far.
+    pub fn committed(&self) -> usize {
+        self.committed
+    }
+
+    /// Total length of the image.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` for an empty image.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Programs the next chunk of the image.
+    ///
+    /// `data` must be exactly `CHUNK` bytes, except for the last chunk which
+    /// holds the remainder of the image.
+    pub fn program_chunk(&mut self, data: &[u8]) -> Result<(), ErrorOf<D, H, J>> {
+        let remaining = self.len - self.committed;
+        // Past the end, the erase below would leave the validated span.
+        if remaining == 0 || data.len() > remaining {
+            return Err(Error::Overrun);
+        }
+        if data.len() > CHUNK || (data.len() < CHUNK && data.len() != remaining) {
+            return Err(Error::InvalidChunk);
+        }
+
+        let addr = self.base + self.committed;
+        // `check_geometry` ensures the padded length stays within `CHUNK`.
+        let padded = data.len().next_multiple_of(self.device.program_size());
+        let mut chunk = [0xFFu8; CHUNK];
+        chunk[..data.len()].copy_from_slice(data);
+
+        self.device
+            .erase(ByteOffset(addr), CHUNK)
+            .map_err(Error::Device)?;
+        self.device
+            .program(ByteOffset(addr), &chunk[..padded])
+            .map_err(Error::Device)?;
+        self.digest
+            .update(&mut chunk[..data.len()])
+            .map_err(Error::Digest)?;
+        self.committed += data.len();
+
+        self.checkpoint()
+    }
+
+    /// Completes the image and writes its digest into `out`.
+    ///
+    /// All journal records of the image are marked complete.
+    pub fn finish(mut self, out: &mut [u8]) -> Result<(D, H, J), ErrorOf<D, H, J>> {
+        if self.committed != self.len {
+            return Err(Error::InvalidChunk);
+        }
+
+        self.digest.finalize(out).map_err(Error::Digest)?;
+        if let Some(id) = self.checkpoint {
+            self.journal.mark_complete(id).map_err(Error::Journal)?;
+        }
+        self.journal
+            .mark_complete(self.image)
+            .map_err(Error::Journal)?;
+        Ok((self.device, self.digest, self.journal))
+    }
+
+    fn checkpoint(&mut self) -> Result<(), ErrorOf<D, H, J>> {
+        let mut payload = [0u8; STATE];
+        payload[..4].copy_from_slice(&(self.committed as u32).to_le_bytes());
+        // Exporting resets the engine, so the context is imported right back.
+        let n = self
+            .digest
+            .export_context(&mut payload[4..])
+            .map_err(Error::Digest)?;
+        self.digest
+            .import_context(&payload[4..4 + n])
+            .map_err(Error::Digest)?;
+
+        let payload = &payload[..4 + n];
+        let id = match self.journal.append_intent(OP_CHECKPOINT, payload) {
+            Err(e) if e.kind() == journal::ErrorKind::Full => {
+                let pending = [Some(self.image), self.checkpoint];
+                if !Self::reclaim(&mut self.journal, &pending).map_err(Error::Journal)? {
+                    return Err(Error::Journal(e));
+                }
+                self.image = self
+                    .journal
+                    .append_intent(OP_IMAGE, &Self::image_payload(self.base, self.len))
+                    .map_err(Error::Journal)?;
+                self.checkpoint = None;
+                self.journal.append_intent(OP_CHECKPOINT, payload)
+            }
+            result => result,
+        }
+        .map_err(Error::Journal)?;
+        if let Some(previous) = self.checkpoint.replace(id) {
+            self.journal
+                .mark_complete(previous)
+                .map_err(Error::Journal)?;
+        }
+        Ok(())
+    }
+
+    /// Clears the journal unless it holds pending records other than `ours`.
+    ///
+    /// Returns `true` if the journal was cleared.
+    fn reclaim(journal: &mut J, ours: &[Option<RecordId>]) -> Result<bool, J::Error> {
+        let mut from = 0;
+        while let Some(intent) = journal.scan_incomplete(from, &mut [])? {
+            if !ours.contains(&Some(intent.id)) {
+                return Ok(false);
+            }
+            from = intent.id + 1;
+        }
+        journal.clear()?;
+        Ok(true)
+    }
+
+    fn image_payload(base: usize, len: usize) -> [u8; 8] {
+        let mut payload = [0u8; 8];
+        payload[..4].copy_from_slice(&(base as u32).to_le_bytes());
+        payload[4..].copy_from_slice(&(len as u32).to_le_bytes());
+        payload
+    }
+
+    fn check_geometry(device: &D, base: usize, len: usize) -> Result<(), ErrorOf<D, H, J>> {
+        let erase = device.erase_size();
+        let unit = device.program_size();
+        let span = len.checked_next_multiple_of(CHUNK);
+        // The last chunk may be shorter than a program page.
+        if !device.program_capabilities().partial_page
+            || erase == 0
+            || unit == 0
+            || CHUNK == 0
+            || !is_aligned(CHUNK, erase)
+            || !is_aligned(CHUNK, unit)
+            || base > u32::MAX as usize
+            || len > u32::MAX as usize
+            || span.is_none_or(|span| check_aligned(base, span, erase, device.capacity()).is_err())
+        {
+            return Err(Error::InvalidGeometry);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::AlgorithmId;
+    use crate::block_device::ProgramCapabilities;
+    use crate::digest::{self, Digest, MigrationTag};
+    use crate::journal::BlockJournal;
+    use crate::testutil::NorMock;
+
+    /// FNV-1a whose context can be exported and imported.
+    struct Fnv(u32);
+
+    impl Default for Fnv {
+        fn default() -> Self {
+            Self(0x811C_9DC5)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Incompatible;
+
+    impl digest::Error for Incompatible {
+        fn kind(&self) -> digest::ErrorKind {
+            digest::ErrorKind::IncompatibleContext
+        }
+    }
+
+    impl digest::ErrorType for Fnv {
+        type Error = Incompatible;
+    }
+
+    impl Digest for Fnv {
+        type InitParams = ();
+
+        const OUTPUT_SIZE: usize = 4;
+
+        const BLOCK_SIZE: usize = 1;
+
+        fn init(_init_params: ()) -> Result<(), Incompatible> {
+            Ok(())
+        }
+
+        fn update(&mut self, input: &mut [u8]) -> Result<(), Incompatible> {
+            for &b in input.iter() {
+                self.0 = (self.0 ^ b as u32).wrapping_mul(0x0100_0193);
+            }
+            Ok(())
+        }
+
+        fn reset(&mut self) -> Result<(), Incompatible> {
+            *self = Self::default();
+            Ok(())
+        }
+
+        fn finalize(&mut self, out: &mut [u8]) -> Result<(), Incompatible> {
+            out[..4].copy_from_slice(&self.0.to_le_bytes());
+            self.reset()
+        }
+    }
+
+    impl DigestMigrate for Fnv {
+        fn migration_tag(&self) -> MigrationTag {
+            MigrationTag {
+                algorithm: AlgorithmId::vendor(0x7E57, 1),
+                format: 1,
+            }
+        }
+
+        fn export_context(&mut self, out: &mut [u8]) -> Result<usize, Incompatible> {
+            out[..MigrationTag::SIZE].copy_from_slice(&self.migration_tag().to_bytes());
+            out[MigrationTag::SIZE..][..4].copy_from_slice(&self.0.to_le_bytes());
+            self.reset()?;
+            Ok(MigrationTag::SIZE + 4)
+        }
+
+        fn import_context(&mut self, blob: &[u8]) -> Result<(), Incompatible> {
+            if MigrationTag::from_bytes(blob) != Some(self.migration_tag()) || blob.len() != 12 {
+                return Err(Incompatible);
+            }
+            self.0 = u32::from_le_bytes(blob[8..].try_into().unwrap());
+            Ok(())
+        }
+    }
+
+    /// Eight 64-byte journal slots.
+    type TestJournal = BlockJournal<NorMock<1024>, 64>;
+    /// 128-byte chunks over 64-byte sectors.
+    type Programmer = ChunkedProgrammer<NorMock<2048>, Fnv, TestJournal, 128, 16>;
+
+    const BASE: ByteOffset = ByteOffset(256);
+
+    fn journal() -> TestJournal {
+        BlockJournal::new(NorMock::new(256), ByteOffset(256), 512).unwrap()
+    }
+
+    fn image<const N: usize>() -> [u8; N] {
+        core::array::from_fn(|i| (i * 7 + i / 256) as u8)
+    }
+
+    fn expected(image: &[u8]) -> [u8; 4] {
+        let mut fnv = Fnv::default();
+        for &b in image {
+            fnv.update(&mut [b]).unwrap();
+        }
+        fnv.finalize_array().unwrap()
+    }
+
+    fn program_all(programmer: &mut Programmer, image: &[u8]) {
+        for chunk in image[programmer.committed()..].chunks(128) {
+            programmer.program_chunk(chunk).unwrap();
+        }
+    }
+
+    #[test]
+    fn programs_full_image() {
+        let data = image::<512>();
+        let mut programmer =
+            Programmer::new(NorMock::new(64), Fnv::default(), journal(), BASE, 512).unwrap();
+        program_all(&mut programmer, &data);
+        assert_eq!(programmer.committed(), 512);
+
+        let mut digest = [0u8; 4];
+        let (device, _, mut journal) = programmer.finish(&mut digest).unwrap();
+        assert_eq!(digest, expected(&data));
+        assert_eq!(device.data[256..768], data);
+        assert_eq!(device.data[768], 0xFF);
+        assert_eq!(journal.scan_incomplete(0, &mut []), Ok(None));
+    }
+
+    #[test]
+    fn resumes_after_interruption() {
+        let data = image::<512>();
+        let mut programmer =
+            Programmer::new(NorMock::new(64), Fnv::default(), journal(), BASE, 512).unwrap();
+        programmer.program_chunk(&data[..128]).unwrap();
+        programmer.program_chunk(&data[128..256]).unwrap();
+        let (mut device, _, journal) = programmer.release();
+        // The chunk in flight at reset is redone from scratch.
+        device.data[512..576].fill(0);
+
+        let mut programmer = Programmer::resume(device, Fnv::default(), journal)
+            .unwrap()
+            .unwrap();
+        assert_eq!(programmer.committed(), 256);
+        assert_eq!(programmer.len(), 512);
+        program_all(&mut programmer, &data);
+
+        let mut digest = [0u8; 4];
+        let (device, _, journal) = programmer.finish(&mut digest).unwrap();
+        assert_eq!(digest, expected(&data));
+        assert_eq!(device.data[256..768], data);
+        assert!(Programmer::resume(device, Fnv::default(), journal)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn resumes_before_first_checkpoint() {
+        let programmer =
+            Programmer::new(NorMock::new(64), Fnv::default(), journal(), BASE, 300).unwrap();
+        let (device, _, journal) = programmer.release();
+        let programmer = Programmer::resume(device, Fnv::default(), journal)
+            .unwrap()
+            .unwrap();
+        assert_eq!((programmer.committed(), programmer.len()), (0, 300));
+    }
+
+    #[test]
+    fn short_final_chunk_is_padded() {
+        let data = image::<300>();
+        let mut device = NorMock::new(64);
+        device.program_size = 16;
+        let mut programmer = Programmer::new(device, Fnv::default(), journal(), BASE, 300).unwrap();
+        program_all(&mut programmer, &data);
+
+        let mut digest = [0u8; 4];
+        let (device, _, _) = programmer.finish(&mut digest).unwrap();
+        assert_eq!(digest, expected(&data));
+        assert_eq!(device.data[256..556], data);
+        assert!(device.data[556..768].iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn invalid_chunks_are_rejected() {
+        let data = image::<512>();
+        let mut programmer =
+            Programmer::new(NorMock::new(64), Fnv::default(), journal(), BASE, 300).unwrap();
+        assert_eq!(
+            programmer.program_chunk(&data[..129]),
+            Err(Error::InvalidChunk)
+        );
+        assert_eq!(
+            programmer.program_chunk(&data[..100]),
+            Err(Error::InvalidChunk)
+        );
+        let mut digest = [0u8; 4];
+        programmer.program_chunk(&data[..128]).unwrap();
+        programmer.program_chunk(&data[..128]).unwrap();
+        assert_eq!(programmer.program_chunk(&data[..45]), Err(Error::Overrun));
+        programmer.program_chunk(&data[..44]).unwrap();
+        assert_eq!(programmer.program_chunk(&data[..1]), Err(Error::Overrun));
+        assert!(programmer.finish(&mut digest).is_ok());
+    }
+
+    #[test]
+    fn invalid_geometry_is_rejected() {
+        // A 48-byte program unit does not divide the 128-byte chunk.
+        let mut device = NorMock::new(64);
+        device.program_size = 48;
+        assert!(matches!(
+            Programmer::new(device, Fnv::default(), journal(), BASE, 300),
+            Err(Error::InvalidGeometry)
+        ));
+
+        let mut device = NorMock::new(64);
+        device.caps = ProgramCapabilities::default();
+        assert!(matches!(
+            Programmer::new(device, Fnv::default(), journal(), BASE, 300),
+            Err(Error::InvalidGeometry)
+        ));
+
+        assert!(matches!(
+            Programmer::new(
+                NorMock::new(64),
+                Fnv::default(),
+                journal(),
+                ByteOffset(32),
+                128
+            ),
+            Err(Error::InvalidGeometry)
+        ));
+        assert!(matches!(
+            Programmer::new(NorMock::new(64), Fnv::default(), journal(), BASE, 1900),
+            Err(Error::InvalidGeometry)
+        ));
+    }
+
+    #[test]
+    fn new_image_abandons_unfinished_one() {
+        let data = image::<512>();
+        let mut programmer =
+            Programmer::new(NorMock::new(64), Fnv::default(), journal(), BASE, 512).unwrap();
+        programmer.program_chunk(&data[..128]).unwrap();
+        let (device, _, journal) = programmer.release();
+
+        let programmer =
+            Programmer::new(device, Fnv::default(), journal, ByteOffset(1024), 256).unwrap();
+        let (device, _, mut journal) = programmer.release();
+        let mut from = 0;
+        let mut pending = 0;
+        while let Some(intent) = journal.scan_incomplete(from, &mut []).unwrap() {
+            assert_eq!(intent.op, OP_IMAGE);
+            pending += 1;
+            from = intent.id + 1;
+        }
+        assert_eq!(pending, 1);
+
+        let programmer = Programmer::resume(device, Fnv::default(), journal)
+            .unwrap()
+            .unwrap();
+        assert_eq!((programmer.committed(), programmer.len()), (0, 256));
+    }
+
+    #[test]
+    fn journal_is_reclaimed_for_long_images() {
+        // Sixteen chunks, twice as many as the journal has slots.
+        let data = image::<2048>();
+        type Long = ChunkedProgrammer<NorMock<2048>, Fnv, TestJournal, 128, 16>;
+        let mut programmer = Long::new(
+            NorMock::new(64),
+            Fnv::default(),
+            journal(),
+            ByteOffset(0),
+            2048,
+        )
+        .unwrap();
+        for (i, chunk) in data.chunks(128).enumerate() {
+            programmer.program_chunk(chunk).unwrap();
+            if i == 10 {
+                let (device, digest, journal) = programmer.release();
+                programmer = Long::resume(device, digest, journal).unwrap().unwrap();
+                assert_eq!(programmer.committed(), 11 * 128);
+            }
+        }
+
+        let mut digest = [0u8; 4];
+        let (device, _, _) = programmer.finish(&mut digest).unwrap();
+        assert_eq!(digest, expected(&data));
+        assert_eq!(device.data, data);
+    }
+
+    #[test]
+    fn foreign_records_are_not_reclaimed() {
+        let data = image::<2048>();
+        let mut journal = journal();
+        journal.append_intent(0x1234, &[]).unwrap();
+        let mut programmer = Programmer::new(
+            NorMock::new(64),
+            Fnv::default(),
+            journal,
+            ByteOffset(0),
+            2048,
+        )
+        .unwrap();
+        for chunk in data.chunks(128).take(6) {
+            programmer.program_chunk(chunk).unwrap();
+        }
+        assert!(matches!(
+            programmer.program_chunk(&data[768..896]),
+            Err(Error::Journal(crate::journal::BlockJournalError::Full))
+        ));
+    }
+}
@@ -0,0 +1,573 @@
+//! Block ciphers and their modes of operation.
+
+use crate::algorithm::AlgorithmId;
+use crate::common::{ConstantTimeEq, Zeroize};
+use crate::contract::ContractVersion;
+
+/// Error kind.
+///
+/// This represents a common set of cipher operation errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// The key length is not valid for the cipher and mode.
+    InvalidKeyLength,
+
+    /// The IV or tweak length is not valid for the cipher and mode.
+    InvalidIvLength,
+
+    /// The data length is not valid for the mode, e.g. not a multiple of the block size.
+    InvalidDataLength,
+
+    /// The cipher or mode is not supported by the implementation.
+    UnsupportedAlgorithm,
+
+    /// Unwrapped or decrypted data failed its integrity check.
+    IntegrityCheckFailed,
+
+    /// General hardware failure.
+    HardwareFailure,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Block cipher marker.
+pub trait BlockCipher {
+    /// Algorithm identifier.
+    const ID: AlgorithmId;
+    /// Size of a block in bytes.
+    const BLOCK_SIZE: usize;
+    /// Size of a key in bytes.
+    const KEY_SIZE: usize;
+}
+
+/// AES (FIPS 197) with a 128-bit key.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Aes128;
+
+impl BlockCipher for Aes128 {
+    const ID: AlgorithmId = AlgorithmId::AES_128;
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 16;
+}
+
+/// AES (FIPS 197) with a 192-bit key.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Aes192;
+
+impl BlockCipher for Aes192 {
+    const ID: AlgorithmId = AlgorithmId::AES_192;
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 24;
+}
+
+/// AES (FIPS 197) with a 256-bit key.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Aes256;
+
+impl BlockCipher for Aes256 {
+    const ID: AlgorithmId = AlgorithmId::AES_256;
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 32;
+}
+
+/// SM4 (GB/T 32907-2016).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Sm4;
+
+impl BlockCipher for Sm4 {
+    const ID: AlgorithmId = AlgorithmId::SM4;
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 16;
+}
+
+/// Mode of operation marker.
+pub trait CipherMode {
+    /// Number of cipher blocks of IV (or tweak) the mode takes.
+    const IV_BLOCKS: usize;
+    /// Number of cipher keys the mode takes, concatenated in the key passed to `init`.
+    const KEY_COUNT: usize;
+    /// Whether the data of each call must be a multiple of the block size.
+    const BLOCK_ALIGNED: bool;
+}
+
+/// Electronic codebook. Only suitable for single blocks, e.g. key material.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Ecb;
+
+impl CipherMode for Ecb {
+    const IV_BLOCKS: usize = 0;
+    const KEY_COUNT: usize = 1;
+    const BLOCK_ALIGNED: bool = true;
+}
+
+/// Cipher block chaining (NIST SP 800-38A), without padding.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Cbc;
+
+impl CipherMode for Cbc {
+    const IV_BLOCKS: usize = 1;
+    const KEY_COUNT: usize = 1;
+    const BLOCK_ALIGNED: bool = true;
+}
+
+/// Counter mode (NIST SP 800-38A) with a big-endian counter block.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Ctr;
+
+impl CipherMode for Ctr {
+    const IV_BLOCKS: usize = 1;
+    const KEY_COUNT: usize = 1;
+    const BLOCK_ALIGNED: bool = false;
+}
+
+/// XTS (IEEE 1619, NIST SP 800-38E) for storage encryption.
+///
+/// Each call processes one data unit (e.g. a flash sector) of at least one
+/// block; set the tweak of the next data unit with [`CipherOp::set_iv`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Xts;
+
+impl CipherMode for Xts {
+    const IV_BLOCKS: usize = 1;
+    const KEY_COUNT: usize = 2;
+    const BLOCK_ALIGNED: bool = false;
+}
+
+/// Cipher engine supporting cipher `C` in mode `M`.
+pub trait CipherInit<C: BlockCipher, M: CipherMode>: ErrorType {
+    /// Operation processing one message.
+    type Op<'a>: CipherOp<Error = Self::Error>
+    where
+        Self: 'a;
+
+    /// Starts a cipher operation.
+    ///
+    /// # Parameters
+    ///
+    /// - `cipher`: The cipher marker.
+    /// - `mode`: The mode marker.
+    /// - `key`: `M::KEY_COUNT * C::KEY_SIZE` bytes of key.
+    /// - `iv`: `M::IV_BLOCKS * C::BLOCK_SIZE` bytes of IV, counter block or tweak.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the keyed operation, or an error.
+    fn init<'a>(
+        &'a mut self,
+        cipher: C,
+        mode: M,
+        key: &[u8],
+        iv: &[u8],
+    ) -> Result<Self::Op<'a>, Self::Error>;
+}
+
+/// Keyed cipher operation.
+///
+/// Chained modes carry their state (CBC chaining value, CTR counter) from one
+/// call to the next, so a message may be processed in several calls. Do not
+/// mix encryption and decryption without setting a new IV in between.
+pub trait CipherOp: ErrorType {
+    /// Encrypts `data` in place.
+    ///
+    /// # Parameters
+    ///
+    /// - `data`: Plaintext, replaced by the ciphertext.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. Fails with `ErrorKind::InvalidDataLength` if the length is not valid for the mode.
+    fn encrypt_in_place(&mut self, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Decrypts `data` in place.
+    ///
+    /// # Parameters
+    ///
+    /// - `data`: Ciphertext, replaced by the plaintext.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. Fails with `ErrorKind::InvalidDataLength` if the length is not valid for the mode.
+    fn decrypt_in_place(&mut self, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Sets a new IV, counter block or tweak, starting a new message or data unit.
+    ///
+    /// # Parameters
+    ///
+    /// - `iv`: The IV, of the length given to `init`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn set_iv(&mut self, iv: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Bytes added to a key by [`KeyWrap::wrap_key`].
+pub const KEY_WRAP_OVERHEAD: usize = 8;
+
+/// Key wrapping (AES-KW, RFC 3394 / NIST SP 800-38F) under a key-encryption key.
+///
+/// Implemented by key vaults that hold the KEK internally as well as by
+/// [`AesKw`] over any ECB engine, so either can provision OTP-stored keys.
+pub trait KeyWrap<C: BlockCipher>: ErrorType {
+    /// Key-encryption key: raw key bytes or a handle to a key in a vault.
+    type Kek: ?Sized;
+
+    /// Wraps `key` under `kek`.
+    ///
+    /// # Parameters
+    ///
+    /// - `kek`: The key-encryption key.
+    /// - `key`: The key to wrap; a multiple of 8 bytes, at least 16.
+    /// - `out`: Receives the wrapped key, `key.len() + KEY_WRAP_OVERHEAD` bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written, or an error.
+    fn wrap_key(
+        &mut self,
+        kek: &Self::Kek,
+        key: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Self::Error>;
+}
+
+/// Key unwrapping, the inverse of [`KeyWrap`].
+pub trait KeyUnwrap<C: BlockCipher>: ErrorType {
+    /// Key-encryption key: raw key bytes or a handle to a key in a vault.
+    type Kek: ?Sized;
+
+    /// Unwraps `wrapped` under `kek` and checks its integrity.
+    ///
+    /// # Parameters
+    ///
+    /// - `kek`: The key-encryption key.
+    /// - `wrapped`: The wrapped key.
+    /// - `out`: Receives the key, `wrapped.len() - KEY_WRAP_OVERHEAD` bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written. Fails with `ErrorKind::IntegrityCheckFailed` if the wrapped key was modified or the KEK is wrong; `out` is then zeroed.
+    fn unwrap_key(
+        &mut self,
+        kek: &Self::Kek,
+        wrapped: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Self::Error>;
+}
+
+/// Error returned by [`AesKw`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyWrapError<E> {
+    /// The cipher engine failed.
+    Cipher(E),
+    /// The key, wrapped key or output buffer has an invalid length.
+    InvalidLength,
+    /// The integrity check of an unwrapped key failed.
+    IntegrityCheckFailed,
+}
+
+impl<E: Error> Error for KeyWrapError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            KeyWrapError::Cipher(e) => e.kind(),
+            KeyWrapError::InvalidLength => ErrorKind::InvalidDataLength,
+            KeyWrapError::IntegrityCheckFailed => ErrorKind::IntegrityCheckFailed,
+        }
+    }
+}
+
+const KEY_WRAP_IV: [u8; 8] = [0xA6; 8];
+
+/// Software AES-KW over an ECB engine; the KEK is passed as raw key bytes.
+pub struct AesKw<E> {
+    engine: E,
+}
+
+impl<E> AesKw<E> {
+    pub fn new(engine: E) -> Self {
+        Self { engine }
+    }
+
+    /// Releases the cipher engine.
+    pub fn release(self) -> E {
+        self.engine
+    }
+}
+
+fn is_wrappable_len(key_len: usize) -> bool {
+    key_len >= 16 && key_len.is_multiple_of(8)
+}
+
+/// Wrapping rounds of RFC 3394 section 2.2.1 over `a` and the key in `r`.
+fn wrap_rounds<O: CipherOp>(
+    op: &mut O,
+    a: &mut [u8],
+    r: &mut [u8],
+    block: &mut [u8; 16],
+) -> Result<(), O::Error> {
+    let n = r.len() / 8;
+    for j in 0..6 {
+        for i in 0..n {
+            let ri = &mut r[i * 8..i * 8 + 8];
+            block[..8].copy_from_slice(a);
+            block[8..].copy_from_slice(ri);
+            op.encrypt_in_place(block)?;
+            let t = ((n * j + i + 1) as u64).to_be_bytes();
+            for (a, (b, t)) in a.iter_mut().zip(block[..8].iter().zip(t)) {
+                *a = b ^ t;
+            }
+            ri.copy_from_slice(&block[8..]);
+        }
+    }
+    Ok(())
+}
+
+/// Unwrapping rounds of RFC 3394 section 2.2.2 over `a` and the key in `r`.
+fn unwrap_rounds<O: CipherOp>(
+    op: &mut O,
+    a: &mut [u8; 8],
+    r: &mut [u8],
+    block: &mut [u8; 16],
+) -> Result<(), O::Error> {
+    let n = r.len() / 8;
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let ri = &mut r[i * 8..i * 8 + 8];
+            let t = ((n * j + i + 1) as u64).to_be_bytes();
+            for (b, (a, t)) in block[..8].iter_mut().zip(a.iter().zip(t)) {
+                *b = a ^ t;
+            }
+            block[8..].copy_from_slice(ri);
+            op.decrypt_in_place(block)?;
+            a.copy_from_slice(&block[..8]);
+            ri.copy_from_slice(&block[8..]);
+        }
+    }
+    Ok(())
+}
+
+impl<E: ErrorType> ErrorType for AesKw<E> {
+    type Error = KeyWrapError<E::Error>;
+}
+
+impl<C, E> KeyWrap<C> for AesKw<E>
+where
+    C: BlockCipher + Default,
+    E: CipherInit<C, Ecb>,
+{
+    type Kek = [u8];
+
+    fn wrap_key(&mut self, kek: &[u8], key: &[u8], out: &mut [u8]) -> Result<usize, Self::Error> {
+        const { assert!(C::BLOCK_SIZE == 16, "AES-KW needs a 128-bit block cipher") };
+        if !is_wrappable_len(key.len()) || out.len() < key.len() + KEY_WRAP_OVERHEAD {
+            return Err(KeyWrapError::InvalidLength);
+        }
+        let mut op = self
+            .engine
+            .init(C::default(), Ecb, kek, &[])
+            .map_err(KeyWrapError::Cipher)?;
+
+        let wrapped = &mut out[..key.len() + KEY_WRAP_OVERHEAD];
+        let (a, r) = wrapped.split_at_mut(8);
+        a.copy_from_slice(&KEY_WRAP_IV);
+        r.copy_from_slice(key);
+        let mut block = [0u8; 16];
+        let result = wrap_rounds(&mut op, a, r, &mut block);
+        block.zeroize();
+        if let Err(e) = result {
+            // `r` still holds partially wrapped key bytes.
+            wrapped.zeroize();
+            return Err(KeyWrapError::Cipher(e));
+        }
+        Ok(key.len() + KEY_WRAP_OVERHEAD)
+    }
+}
+
+impl<C, E> KeyUnwrap<C> for AesKw<E>
+where
+    C: BlockCipher + Default,
+    E: CipherInit<C, Ecb>,
+{
+    type Kek = [u8];
+
+    fn unwrap_key(
+        &mut self,
+        kek: &[u8],
+        wrapped: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Self::Error> {
+        const { assert!(C::BLOCK_SIZE == 16, "AES-KW needs a 128-bit block cipher") };
+        let len = wrapped.len().saturating_sub(KEY_WRAP_OVERHEAD);
+        if !is_wrappable_len(len) || out.len() < len {
+            return Err(KeyWrapError::InvalidLength);
+        }
+        let mut op = self
+            .engine
+            .init(C::default(), Ecb, kek, &[])
+            .map_err(KeyWrapError::Cipher)?;
+
+        let mut a = [0u8; 8];
+        a.copy_from_slice(&wrapped[..8]);
+        let r = &mut out[..len];
+        r.copy_from_slice(&wrapped[8..]);
+        let mut block = [0u8; 16];
+        let result = unwrap_rounds(&mut op, &mut a, r, &mut block);
+        block.zeroize();
+        // Nothing is released unless the integrity check passes.
+        let error = match result {
+            Err(e) => Some(KeyWrapError::Cipher(e)),
+            Ok(()) if !a.ct_eq(&KEY_WRAP_IV) => Some(KeyWrapError::IntegrityCheckFailed),
+            Ok(()) => None,
+        };
+        if let Some(error) = error {
+            r.zeroize();
+            return Err(error);
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{hex, SoftAes};
+
+    const KEK_128: &str = "000102030405060708090A0B0C0D0E0F";
+    const KEK_192: &str = "000102030405060708090A0B0C0D0E0F1011121314151617";
+    const KEK_256: &str = "000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F";
+    const KEY_128: &str = "00112233445566778899AABBCCDDEEFF";
+    const KEY_192: &str = "00112233445566778899AABBCCDDEEFF0001020304050607";
+    const KEY_256: &str = "00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F";
+
+    /// Wraps `key` under `kek`, compares with `expected` and unwraps it again.
+    fn check<C>(kek: &[u8], key: &[u8], expected: &[u8])
+    where
+        C: BlockCipher + Default,
+        AesKw<SoftAes>: KeyWrap<C, Kek = [u8]> + KeyUnwrap<C, Kek = [u8]>,
+    {
+        let mut kw = AesKw::new(SoftAes);
+        let mut wrapped = [0u8; 40];
+        let n = KeyWrap::<C>::wrap_key(&mut kw, kek, key, &mut wrapped).unwrap();
+        assert_eq!(&wrapped[..n], expected);
+
+        let mut unwrapped = [0u8; 32];
+        let n = KeyUnwrap::<C>::unwrap_key(&mut kw, kek, expected, &mut unwrapped).unwrap();
+        assert_eq!(&unwrapped[..n], key);
+    }
+
+    #[test]
+    fn rfc3394_vectors() {
+        // Sections 4.1 to 4.6.
+        check::<Aes128>(
+            &hex::<16>(KEK_128),
+            &hex::<16>(KEY_128),
+            &hex::<24>("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5"),
+        );
+        check::<Aes192>(
+            &hex::<24>(KEK_192),
+            &hex::<16>(KEY_128),
+            &hex::<24>("96778B25AE6CA435F92B5B97C050AED2468AB8A17AD84E5D"),
+        );
+        check::<Aes256>(
+            &hex::<32>(KEK_256),
+            &hex::<16>(KEY_128),
+            &hex::<24>("64E8C3F9CE0F5BA263E9777905818A2A93C8191E7D6E8AE7"),
+        );
+        check::<Aes192>(
+            &hex::<24>(KEK_192),
+            &hex::<24>(KEY_192),
+            &hex::<32>("031D33264E15D33268F24EC260743EDCE1C6C7DDEE725A936BA814915C6762D2"),
+        );
+        check::<Aes256>(
+            &hex::<32>(KEK_256),
+            &hex::<24>(KEY_192),
+            &hex::<32>("A8F9BC1612C68B3FF6E6F4FBE30E71E4769C8B80A32CB8958CD5D17D6B254DA1"),
+        );
+        check::<Aes256>(
+            &hex::<32>(KEK_256),
+            &hex::<32>(KEY_256),
+            &hex::<40>(
+                "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326\
+                 CBC7F0E71A99F43BFB988B9B7A02DD21",
+            ),
+        );
+    }
+
+    #[test]
+    fn tampered_wrap_fails_integrity_check_and_clears_output() {
+        let mut wrapped = hex::<24>("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5");
+        wrapped[23] ^= 1;
+        let mut out = [0x55u8; 16];
+        let result = KeyUnwrap::<Aes128>::unwrap_key(
+            &mut AesKw::new(SoftAes),
+            &hex::<16>(KEK_128),
+            &wrapped,
+            &mut out,
+        );
+        assert_eq!(result, Err(KeyWrapError::IntegrityCheckFailed));
+        assert_eq!(out, [0; 16]);
+    }
+
+    #[test]
+    fn invalid_lengths_are_rejected() {
+        let kek = hex::<16>(KEK_128);
+        let mut kw = AesKw::new(SoftAes);
+        let mut out = [0u8; 40];
+        // Shorter than two semiblocks, not a multiple of 8, output too small.
+        assert_eq!(
+            KeyWrap::<Aes128>::wrap_key(&mut kw, &kek, &[0; 8], &mut out),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            KeyWrap::<Aes128>::wrap_key(&mut kw, &kek, &[0; 20], &mut out),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            KeyWrap::<Aes128>::wrap_key(&mut kw, &kek, &[0; 16], &mut out[..23]),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            KeyUnwrap::<Aes128>::unwrap_key(&mut kw, &kek, &[0; 16], &mut out),
+            Err(KeyWrapError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,488 @@
+//! Byte serialization shared by the peripheral traits.
+//!
+//! Keys, signatures and configuration words cross register files, fuse
+//! arrays and wire formats with differing byte orders, so conversions take
+//! the byte order explicitly and write into caller buffers.
+//!
+//! Secrets handled by the traits, such as private keys, shared secrets and
+//! MAC tags, are compared with [`ConstantTimeEq`] and cleared with
+//! [`Zeroize`].
+
+use core::sync::atomic::{compiler_fence, Ordering};
+
+/// Derive macros for [`ToBytes`] and [`FromBytes`] on plain structs.
+///
+/// Fields are encoded back to back in declaration order. A
+/// `#[bytes(endian = "little")]` or `#[bytes(endian = "big")]` attribute on
+/// the struct or on a field fixes the byte order regardless of the one
+/// passed in; a field attribute overrides the struct attribute. Deriving
+/// `FromBytes` also derives [`FixedLen`].
+///
+/// ```
+/// use peripheral_traits::common::{Endian, FromBytes, ToBytes};
+///
+/// #[derive(Debug, PartialEq, ToBytes, FromBytes)]
+/// #[bytes(endian = "little")]
+/// struct DeviceConfig {
+///     flags: u32,
+///     #[bytes(endian = "big")]
+///     vendor_id: u16,
+///     serial: [u8; 6],
+/// }
+///
+/// let config = DeviceConfig { flags: 1, vendor_id: 0x1234, serial: *b"ABCDEF" };
+/// let mut buf = [0u8; 12];
+/// assert_eq!(config.to_bytes(&mut buf, Endian::Big), Ok(12));
+/// assert_eq!(&buf, b"\x01\0\0\0\x12\x34ABCDEF");
+/// assert_eq!(DeviceConfig::from_bytes(&buf, Endian::Big), Ok(config));
+/// ```
+#[cfg(feature = "derive")]
+pub use peripheral_traits_derive::{FromBytes, ToBytes};
+
+/// Byte order of a serialized value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+/// Error returned by [`ToBytes`] and [`FromBytes`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BytesError {
+    /// The destination buffer is shorter than [`ToBytes::byte_len`].
+    BufferTooSmall,
+    /// The source has the wrong length for the type.
+    InvalidLength,
+    /// The source bytes do not encode a valid value.
+    InvalidValue,
+}
+
+/// Serialization into a caller-provided buffer.
+pub trait ToBytes {
+    /// Number of bytes [`to_bytes`](Self::to_bytes) writes.
+    fn byte_len(&self) -> usize;
+
+    /// Writes the value to the start of `dest`.
+    ///
+    /// # Parameters
+    ///
+    /// - `dest`: Buffer of at least `byte_len()` bytes.
+    /// - `endian`: Byte order of multi-byte fields. Byte strings are copied
+    ///   as is.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<usize, BytesError>;
+}
+
+/// Deserialization from a byte slice.
+pub trait FromBytes: Sized {
+    /// Parses a value from exactly `bytes`.
+    ///
+    /// # Parameters
+    ///
+    /// - `bytes`: The encoded value.
+    /// - `endian`: Byte order of multi-byte fields.
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Result<Self, BytesError>;
+}
+
+/// Types whose encoding always has the same length, e.g. fields of a
+/// derived [`FromBytes`] struct.
+pub trait FixedLen {
+    /// Length of the encoding in bytes.
+    const BYTE_LEN: usize;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {$(
+        impl ToBytes for $t {
+            fn byte_len(&self) -> usize {
+                core::mem::size_of::<$t>()
+            }
+
+            fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<usize, BytesError> {
+                let bytes = match endian {
+                    Endian::Little => self.to_le_bytes(),
+                    Endian::Big => self.to_be_bytes(),
+                };
+                dest.get_mut(..bytes.len())
+                    .ok_or(BytesError::BufferTooSmall)?
+                    .copy_from_slice(&bytes);
+                Ok(bytes.len())
+            }
+        }
+
+        impl FixedLen for $t {
+            const BYTE_LEN: usize = core::mem::size_of::<$t>();
+        }
+
+        impl FromBytes for $t {
+            fn from_bytes(bytes: &[u8], endian: Endian) -> Result<Self, BytesError> {
+                let bytes = bytes.try_into().map_err(|_| BytesError::InvalidLength)?;
+                Ok(match endian {
+                    Endian::Little => <$t>::from_le_bytes(bytes),
+                    Endian::Big => <$t>::from_be_bytes(bytes),
+                })
+            }
+        }
+    )*};
+}
+
+impl_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl<const N: usize> ToBytes for [u8; N] {
+    fn byte_len(&self) -> usize {
+        N
+    }
+
+    fn to_bytes(&self, dest: &mut [u8], _endian: Endian) -> Result<usize, BytesError> {
+        dest.get_mut(..N)
+            .ok_or(BytesError::BufferTooSmall)?
+            .copy_from_slice(self);
+        Ok(N)
+    }
+}
+
+impl<const N: usize> FixedLen for [u8; N] {
+    const BYTE_LEN: usize = N;
+}
+
+impl<const N: usize> FromBytes for [u8; N] {
+    fn from_bytes(bytes: &[u8], _endian: Endian) -> Result<Self, BytesError> {
+        bytes.try_into().map_err(|_| BytesError::InvalidLength)
+    }
+}
+
+impl ToBytes for [u8] {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn to_bytes(&self, dest: &mut [u8], _endian: Endian) -> Result<usize, BytesError> {
+        dest.get_mut(..self.len())
+            .ok_or(BytesError::BufferTooSmall)?
+            .copy_from_slice(self);
+        Ok(self.len())
+    }
+}
+
+/// Equality in time independent of the contents compared.
+///
+/// Only lengths, which are public for keys, tags and digests, may affect
+/// the timing.
+pub trait ConstantTimeEq {
+    /// Returns `true` if `self` and `other` are equal.
+    fn ct_eq(&self, other: &Self) -> bool;
+}
+
+impl ConstantTimeEq for [u8] {
+    fn ct_eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let diff = self
+            .iter()
+            .zip(other)
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y));
+        core::hint::black_box(diff) == 0
+    }
+}
+
+impl<const N: usize> ConstantTimeEq for [u8; N] {
+    fn ct_eq(&self, other: &Self) -> bool {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+/// Clearing of secret material in a way the compiler does not elide.
+///
+/// Implementations for handles to engine-held keys release or clear the
+/// key in the engine.
+pub trait Zeroize {
+    /// Overwrites the value with zeros.
+    fn zeroize(&mut self);
+}
+
+impl Zeroize for [u8] {
+    fn zeroize(&mut self) {
+        self.fill(0);
+        // The crate denies unsafe code, so volatile writes are not an
+        // option; handing the buffer to `black_box` keeps the stores alive.
+        core::hint::black_box(&mut *self);
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl<const N: usize> Zeroize for [u8; N] {
+    fn zeroize(&mut self) {
+        self[..].zeroize()
+    }
+}
+
+/// Byte buffer of up to `N` bytes, for outputs whose length is only known
+/// at run time, such as digests and signatures, on targets without an
+/// allocator.
+///
+/// Bytes past the length are kept zeroed, so shrinking the buffer does not
+/// leave stale contents behind.
+#[derive(Copy, Clone)]
+pub struct FixedBuf<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedBuf<N> {
+    /// Empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Copies `data` into a new buffer, or returns `None` if it is longer
+    /// than `N`.
+    pub fn from_slice(data: &[u8]) -> Option<Self> {
+        let mut buf = Self::new();
+        buf.resize(data.len())?.copy_from_slice(data);
+        Some(buf)
+    }
+
+    /// Sets the length to `len` and returns the contents for filling in,
+    /// or `None` if `len` exceeds `N`.
+    ///
+    /// Bytes up to the smaller of both lengths are kept, the rest is zeroed.
+    pub fn resize(&mut self, len: usize) -> Option<&mut [u8]> {
+        if len > N {
+            return None;
+        }
+        self.bytes[len..].zeroize();
+        self.len = len;
+        Some(&mut self.bytes[..len])
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Largest length the buffer can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for FixedBuf<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("FixedBuf").field(&self.as_ref()).finish()
+    }
+}
+
+impl<const N: usize> Default for FixedBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for FixedBuf<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for FixedBuf<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> PartialEq for FixedBuf<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<const N: usize> Eq for FixedBuf<N> {}
+
+impl<const N: usize> ToBytes for FixedBuf<N> {
+    fn byte_len(&self) -> usize {
+        self.len
+    }
+
+    fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<usize, BytesError> {
+        self.as_ref().to_bytes(dest, endian)
+    }
+}
+
+impl<const N: usize> FromBytes for FixedBuf<N> {
+    fn from_bytes(bytes: &[u8], _endian: Endian) -> Result<Self, BytesError> {
+        Self::from_slice(bytes).ok_or(BytesError::InvalidLength)
+    }
+}
+
+impl<const N: usize> ConstantTimeEq for FixedBuf<N> {
+    fn ct_eq(&self, other: &Self) -> bool {
+        self.as_ref().ct_eq(other.as_ref())
+    }
+}
+
+impl<const N: usize> Zeroize for FixedBuf<N> {
+    fn zeroize(&mut self) {
+        self.bytes.zeroize();
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    /// `fmt::Write` sink for checking `Debug` output without an allocator.
+    struct Sink {
+        buf: [u8; 64],
+        len: usize,
+    }
+
+    impl Write for Sink {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let dest = self
+                .buf
+                .get_mut(self.len..self.len + s.len())
+                .ok_or(core::fmt::Error)?;
+            dest.copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            Ok(())
+        }
+    }
+
+    fn debug(value: &impl core::fmt::Debug) -> Sink {
+        let mut sink = Sink {
+            buf: [0; 64],
+            len: 0,
+        };
+        write!(sink, "{value:?}").unwrap();
+        sink
+    }
+
+    #[test]
+    fn fixed_buf_holds_up_to_capacity() {
+        let buf = FixedBuf::<4>::from_slice(b"abc").unwrap();
+        assert_eq!(buf.as_ref(), b"abc");
+        assert_eq!((buf.len(), buf.capacity()), (3, 4));
+        assert!(!buf.is_empty());
+        assert!(FixedBuf::<4>::new().is_empty());
+        assert!(FixedBuf::<4>::from_slice(b"abcde").is_none());
+
+        let mut buf = buf;
+        assert!(buf.resize(5).is_none());
+        assert_eq!(buf.as_ref(), b"abc");
+    }
+
+    #[test]
+    fn fixed_buf_resize_clears_the_tail() {
+        let mut buf = FixedBuf::<4>::from_slice(b"abcd").unwrap();
+        assert_eq!(buf.resize(1).unwrap(), b"a");
+        assert_eq!(buf.bytes, *b"a\0\0\0");
+        assert_eq!(buf.resize(3).unwrap(), b"a\0\0");
+    }
+
+    #[test]
+    fn fixed_buf_compares_contents_only() {
+        let mut a = FixedBuf::<4>::from_slice(b"ab").unwrap();
+        let b = FixedBuf::<4>::from_slice(b"ab").unwrap();
+        assert_eq!(a, b);
+        assert!(a.ct_eq(&b));
+        a.as_mut()[1] = b'c';
+        assert_ne!(a, b);
+        assert!(!a.ct_eq(&b));
+
+        a.zeroize();
+        assert!(a.is_empty());
+        assert_eq!(a.bytes, [0; 4]);
+    }
+
+    #[test]
+    fn fixed_buf_debug_shows_contents_only() {
+        let mut buf = FixedBuf::<4>::from_slice(b"\x01\x02\x03\x04").unwrap();
+        buf.resize(2);
+        let sink = debug(&buf);
+        assert_eq!(&sink.buf[..sink.len], b"FixedBuf([1, 2])");
+    }
+
+    #[test]
+    fn fixed_buf_bytes_round_trip() {
+        let buf = FixedBuf::<4>::from_slice(b"xyz").unwrap();
+        let mut out = [0u8; 4];
+        assert_eq!(buf.to_bytes(&mut out, Endian::Big), Ok(3));
+        assert_eq!(
+            buf.to_bytes(&mut out[..2], Endian::Big),
+            Err(BytesError::BufferTooSmall)
+        );
+        assert_eq!(
+            FixedBuf::<4>::from_bytes(&out[..3], Endian::Little),
+            Ok(buf)
+        );
+        assert_eq!(
+            FixedBuf::<2>::from_bytes(&out[..3], Endian::Little),
+            Err(BytesError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn integers_encode_in_both_byte_orders() {
+        let mut out = [0u8; 8];
+        assert_eq!(0x0102_0304u32.to_bytes(&mut out, Endian::Little), Ok(4));
+        assert_eq!(out[..4], [4, 3, 2, 1]);
+        assert_eq!(0x0102_0304u32.to_bytes(&mut out, Endian::Big), Ok(4));
+        assert_eq!(out[..4], [1, 2, 3, 4]);
+        assert_eq!(0x0102u16.to_bytes(&mut out, Endian::Little), Ok(2));
+        assert_eq!(out[..2], [2, 1]);
+        assert_eq!((-2i64).to_bytes(&mut out, Endian::Big), Ok(8));
+        assert_eq!(out, [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]);
+        assert_eq!(0xABu8.to_bytes(&mut out, Endian::Big), Ok(1));
+        assert_eq!(out[0], 0xAB);
+        assert_eq!(0u32.byte_len(), 4);
+        assert_eq!(<i16 as FixedLen>::BYTE_LEN, 2);
+        assert_eq!(
+            1u32.to_bytes(&mut out[..3], Endian::Little),
+            Err(BytesError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn integers_decode_in_both_byte_orders() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(
+            u32::from_bytes(&bytes[..4], Endian::Little),
+            Ok(0x0403_0201)
+        );
+        assert_eq!(u32::from_bytes(&bytes[..4], Endian::Big), Ok(0x0102_0304));
+        assert_eq!(
+            u64::from_bytes(&bytes, Endian::Big),
+            Ok(0x0102_0304_0506_0708)
+        );
+        assert_eq!(i16::from_bytes(&[0xFF, 0xFE], Endian::Big), Ok(-2));
+        assert_eq!(i16::from_bytes(&[0xFF, 0xFE], Endian::Little), Ok(-257));
+        assert_eq!(
+            u16::from_bytes(&bytes[..3], Endian::Little),
+            Err(BytesError::InvalidLength)
+        );
+        assert_eq!(
+            u8::from_bytes(&[], Endian::Little),
+            Err(BytesError::InvalidLength)
+        );
+
+        for value in [0, 1, -1, i32::MIN, i32::MAX] {
+            for endian in [Endian::Little, Endian::Big] {
+                let mut out = [0u8; 4];
+                value.to_bytes(&mut out, endian).unwrap();
+                assert_eq!(i32::from_bytes(&out, endian), Ok(value));
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Versioning of the trait contracts.
+//!
+//! Each provider module exports a `TRAIT_CONTRACT_VERSION`, and its
+//! `Contract` trait reports the version a provider was built against
+//! through `contract_version()`. A firmware image talking to a separately
+//! built plugin or host tool compares the two before calling across the
+//! boundary.
+
+/// Version of a module's trait contract.
+///
+/// The major version changes when the traits change incompatibly (a method
+/// signature, a required item, the meaning of a value); the minor version
+/// changes when provided items or error kinds are added.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ContractVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ContractVersion {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Returns `true` if a provider at this version satisfies a caller
+    /// expecting `required`: same major version, and at least the same minor.
+    pub const fn satisfies(self, required: ContractVersion) -> bool {
+        self.major == required.major && self.minor >= required.minor
+    }
+}
@@ -1,11 +1,16 @@
 use core::fmt::Debug;
 
+use crate::algorithm::AlgorithmId;
+use crate::common::FixedBuf;
+use crate::contract::ContractVersion;
+
 /// Error kind.
 ///
 /// This represents a common set of digest operation errors. Implementations are
 /// free to define more specific or additional error types. However, by providing
 /// a mapping to these common errors, generic code can still react to them.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum ErrorKind {
     /// The input data length is not valid for the hash function.
@@ -40,6 +45,28 @@ pub enum ErrorKind {
 
     /// The hash computation context has not been initialized.
     NotInitialized,
+
+    /// The imported context was produced by an incompatible algorithm or engine.
+    IncompatibleContext,
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ErrorKind::InvalidInputLength => "invalid input length",
+            ErrorKind::UnsupportedAlgorithm => "unsupported algorithm",
+            ErrorKind::MemoryAllocationFailure => "memory allocation failed",
+            ErrorKind::InitializationError => "initialization failed",
+            ErrorKind::UpdateError => "update failed",
+            ErrorKind::FinalizationError => "finalization failed",
+            ErrorKind::Busy => "engine busy",
+            ErrorKind::HardwareFailure => "hardware failure",
+            ErrorKind::InvalidOutputSize => "invalid output size",
+            ErrorKind::PermissionDenied => "permission denied",
+            ErrorKind::NotInitialized => "not initialized",
+            ErrorKind::IncompatibleContext => "incompatible context",
+        })
+    }
 }
 
 pub trait Error: core::fmt::Debug {
@@ -57,14 +84,32 @@ impl Error for core::convert::Infallible {
     }
 }
 
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
 pub trait ErrorType {
     /// Error type.
     type Error: Error;
 }
 
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
 pub trait Digest: ErrorType {
     type InitParams;
 
+    /// Size of the hash output in bytes.
+    const OUTPUT_SIZE: usize;
+
+    /// Size of the hash input block in bytes, e.g. 64 for SHA-256.
+    const BLOCK_SIZE: usize;
+
     /// Init instance of the crypto function with the given context.
     ///
     /// # Parameters
@@ -98,10 +143,173 @@ pub trait Digest: ErrorType {
     ///
     /// # Parameters
     ///
-    /// - `out`: A mutable slice to store the hash output. The length of the slice must be at least `MAX_OUTPUT_SIZE`.
+    /// - `out`: A mutable slice to store the hash output. The length of the slice must be at least `OUTPUT_SIZE`.
     ///
     /// # Returns
     ///
     /// A `Result` indicating success or failure. On success, returns `Ok(())`. On failure, returns a `CryptoError`.    
     fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Finalize the computation and return the output as an array.
+    ///
+    /// `N` must equal `OUTPUT_SIZE`; a mismatch fails to compile.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the hash output.
+    fn finalize_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+        const { assert!(N == Self::OUTPUT_SIZE, "array length must equal OUTPUT_SIZE") };
+        let mut out = [0u8; N];
+        self.finalize(&mut out)?;
+        Ok(out)
+    }
+
+    /// Finalize the computation into a [`FixedBuf`], e.g. a `FixedBuf<64>`
+    /// holding the output of any supported hash.
+    ///
+    /// `N` must be at least `OUTPUT_SIZE`; a smaller buffer fails to compile.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the digest, `OUTPUT_SIZE` bytes long.
+    fn finalize_buf<const N: usize>(&mut self) -> Result<FixedBuf<N>, Self::Error> {
+        const { assert!(N >= Self::OUTPUT_SIZE, "buffer must hold OUTPUT_SIZE bytes") };
+        let mut out = FixedBuf::new();
+        // Always fits, see the assertion above.
+        if let Some(bytes) = out.resize(Self::OUTPUT_SIZE) {
+            self.finalize(bytes)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Save and restore of the hashing state.
+///
+/// Lets a hash engine shared between callers (e.g. ROM and firmware using one
+/// SHA block) suspend a long-running digest, serve another caller, and resume
+/// later. Unlike [`DigestMigrate`], the saved state is only meant to be
+/// restored on the same engine.
+pub trait ResumableDigest: Digest {
+    /// Saves the current hashing state.
+    ///
+    /// # Parameters
+    ///
+    /// - `buf`: A mutable slice receiving the saved state.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written. Fails with `ErrorKind::InvalidOutputSize` if `buf` is too small.
+    fn export_state(&self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Restores a previously saved hashing state.
+    ///
+    /// # Parameters
+    ///
+    /// - `buf`: State written by `export_state`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. Fails with `ErrorKind::InvalidInputLength` if `buf` is not a valid state.
+    fn import_state(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Tag identifying the origin of an exported digest context.
+///
+/// Two engines can exchange a context when both the algorithm and the state
+/// format match. The format identifies the serialized state layout, e.g. the
+/// intermediate hash value, processed length and pending block of FIPS 180-4.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MigrationTag {
+    /// Identifier of the hash algorithm.
+    pub algorithm: AlgorithmId,
+    /// Identifier of the serialized state layout.
+    pub format: u32,
+}
+
+impl MigrationTag {
+    /// Size of the serialized tag in bytes.
+    pub const SIZE: usize = 8;
+
+    /// Serializes the tag in little endian byte order.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut out = [0u8; Self::SIZE];
+        out[..4].copy_from_slice(&self.algorithm.raw().to_le_bytes());
+        out[4..].copy_from_slice(&self.format.to_le_bytes());
+        out
+    }
+
+    /// Parses a tag from the start of `bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let algorithm = bytes.get(..4)?.try_into().ok()?;
+        let format = bytes.get(4..Self::SIZE)?.try_into().ok()?;
+        Some(Self {
+            algorithm: AlgorithmId::from_raw(u32::from_le_bytes(algorithm)),
+            format: u32::from_le_bytes(format),
+        })
+    }
+}
+
+/// Transfer of an in-progress digest between engines.
+///
+/// Allows a hash started on one engine (e.g. a security coprocessor) to be
+/// finished on another (e.g. the host CPU) after handoff. The exported blob
+/// starts with the serialized [`MigrationTag`] followed by the engine state.
+pub trait DigestMigrate: Digest {
+    /// Returns the tag describing contexts exported by this instance.
+    fn migration_tag(&self) -> MigrationTag;
+
+    /// Exports the current context and resets this instance.
+    ///
+    /// # Parameters
+    ///
+    /// - `out`: A mutable slice receiving the tag and the serialized state.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written. Fails with `ErrorKind::InvalidOutputSize` if `out` is too small.
+    fn export_context(&mut self, out: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Imports a context exported by a compatible engine.
+    ///
+    /// # Parameters
+    ///
+    /// - `blob`: Bytes produced by `export_context`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. Fails with `ErrorKind::IncompatibleContext` if the tag does not match `migration_tag`.
+    fn import_context(&mut self, blob: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Non-blocking update for engines that complete asynchronously (e.g. DMA).
+///
+/// Instead of blocking inside `update`, input is submitted to the engine and
+/// completion is observed by polling or through a registered callback, so the
+/// caller can interleave other work while hashing proceeds.
+pub trait DigestCompletion: Digest {
+    /// Submits input to the engine without waiting for it to be absorbed.
+    ///
+    /// # Parameters
+    ///
+    /// - `input`: The input data to be hashed. The engine copies what it accepts.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes accepted, which may be less than `input.len()`. Fails with `ErrorKind::Busy` if a previous submission is still in progress.
+    fn submit_update(&mut self, input: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Checks whether all submitted input has been absorbed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `true` once the engine is idle.
+    fn poll_complete(&mut self) -> Result<bool, Self::Error>;
+
+    /// Registers a function called from the completion interrupt.
+    ///
+    /// # Parameters
+    ///
+    /// - `callback`: Function invoked with `context` when submitted input has been absorbed. Pass `None` to unregister.
+    /// - `context`: Opaque value passed back to `callback`.
+    fn set_completion_callback(&mut self, callback: Option<fn(usize)>, context: usize);
 }
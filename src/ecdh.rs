@@ -0,0 +1,106 @@
+//! Elliptic-curve Diffie-Hellman key agreement over the Weierstrass curves
+//! used by ECDSA.
+
+use crate::common::Zeroize;
+use crate::contract::ContractVersion;
+use crate::ecdsa::EcdsaCurve;
+
+/// Error kind.
+///
+/// This represents a common set of key agreement errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// Generating the private key failed, e.g. due to an entropy failure.
+    KeyGenError,
+
+    /// The peer public key is not a valid point on the curve.
+    InvalidPublicKey,
+
+    /// The curve is not supported by the implementation.
+    UnsupportedCurve,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Trait for ECDH key agreement (NIST SP 800-56A) on curve `C`.
+///
+/// Uses the same curve markers as the ECDSA traits, so e.g. SPDM session
+/// key establishment and signing share one curve abstraction.
+pub trait EcdhKeyExchange<C: EcdsaCurve>: ErrorType {
+    /// Private key, possibly a handle to key material held by the engine.
+    type PrivateKey: Zeroize;
+    /// Public key.
+    type PublicKey;
+    /// Shared secret: the x-coordinate of the shared point, big endian.
+    type SharedSecret: AsRef<[u8]> + Zeroize;
+
+    /// Generates an ephemeral key pair.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use.
+    ///
+    /// # Returns
+    /// A result containing the private key and the public key to send to the peer, or an error.
+    fn generate_ephemeral(
+        &mut self,
+        curve: &C,
+    ) -> Result<(Self::PrivateKey, Self::PublicKey), Self::Error>;
+
+    /// Computes the shared secret with a peer.
+    ///
+    /// The private key is consumed, since ephemeral keys must not be reused.
+    /// Implementations validate that `peer_public` is on the curve.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use.
+    /// - `private_key`: Our private key.
+    /// - `peer_public`: The peer's public key.
+    ///
+    /// # Returns
+    /// A result containing the shared secret, or `ErrorKind::InvalidPublicKey` if the peer key is invalid.
+    fn compute_shared_secret(
+        &mut self,
+        curve: &C,
+        private_key: Self::PrivateKey,
+        peer_public: &Self::PublicKey,
+    ) -> Result<Self::SharedSecret, Self::Error>;
+}
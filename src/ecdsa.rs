@@ -1,5 +1,10 @@
 use core::fmt::Debug;
 
+use crate::common::Zeroize;
+use crate::contract::ContractVersion;
+use crate::digest::{self, Digest};
+use crate::key_vault::KeyHandle;
+
 pub trait Error: core::fmt::Debug {
     /// Convert error to a generic error kind
     ///
@@ -9,36 +14,181 @@ pub trait Error: core::fmt::Debug {
     fn kind(&self) -> ErrorKind;
 }
 
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
 pub trait ErrorType {
     /// Error type.
     type Error: Error;
 }
 
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
 /// Error kind.
 ///
 /// This represents a common set of digest operation errors. Implementations are
 /// free to define more specific or additional error types. However, by providing
 /// a mapping to these common errors, generic code can still react to them.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum ErrorKind {
     Busy,
     InvalidSignature,
     KeyGenError,
     SigningError,
+    /// The attestation statement for a generated key could not be produced.
+    AttestationError,
+    /// The curve or hash algorithm is not supported by the implementation.
+    UnsupportedAlgorithm,
+    /// The key handle is invalid or its usage does not permit the operation.
+    InvalidKeyHandle,
     Other,
 }
 
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ErrorKind::Busy => "engine busy",
+            ErrorKind::InvalidSignature => "invalid signature",
+            ErrorKind::KeyGenError => "key generation failed",
+            ErrorKind::SigningError => "signing failed",
+            ErrorKind::AttestationError => "key attestation failed",
+            ErrorKind::UnsupportedAlgorithm => "unsupported algorithm",
+            ErrorKind::InvalidKeyHandle => "invalid key handle",
+            ErrorKind::Other => "other error",
+        })
+    }
+}
+
 pub trait HashMarker {
     fn size() -> usize;
 }
 
 pub trait EcdsaCurve {
     fn id() -> u32;
+
+    /// Size of a scalar (private key, signature component) in bytes.
+    const SCALAR_BYTES: usize;
+
+    /// Size of an uncompressed SEC1 point encoding (`0x04 || x || y`) in bytes.
+    const POINT_BYTES: usize = 1 + 2 * Self::SCALAR_BYTES;
+}
+
+/// NIST P-256 (`secp256r1`).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct P256;
+
+impl EcdsaCurve for P256 {
+    /// TLS `NamedGroup` code point.
+    fn id() -> u32 {
+        23
+    }
+
+    const SCALAR_BYTES: usize = 32;
+}
+
+/// NIST P-384 (`secp384r1`).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct P384;
+
+impl EcdsaCurve for P384 {
+    /// TLS `NamedGroup` code point.
+    fn id() -> u32 {
+        24
+    }
+
+    const SCALAR_BYTES: usize = 48;
+}
+
+/// NIST P-521 (`secp521r1`).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct P521;
+
+impl EcdsaCurve for P521 {
+    /// TLS `NamedGroup` code point.
+    fn id() -> u32 {
+        25
+    }
+
+    const SCALAR_BYTES: usize = 66;
+}
+
+/// `secp256k1` (SEC 2).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Secp256k1;
+
+impl EcdsaCurve for Secp256k1 {
+    /// TLS `NamedGroup` code point.
+    fn id() -> u32 {
+        22
+    }
+
+    const SCALAR_BYTES: usize = 32;
+}
+
+/// `brainpoolP256r1` (RFC 5639).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct BrainpoolP256r1;
+
+impl EcdsaCurve for BrainpoolP256r1 {
+    /// TLS `NamedGroup` code point.
+    fn id() -> u32 {
+        26
+    }
+
+    const SCALAR_BYTES: usize = 32;
+}
+
+/// `brainpoolP384r1` (RFC 5639).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct BrainpoolP384r1;
+
+impl EcdsaCurve for BrainpoolP384r1 {
+    /// TLS `NamedGroup` code point.
+    fn id() -> u32 {
+        27
+    }
+
+    const SCALAR_BYTES: usize = 48;
+}
+
+/// SM3 hash (GB/T 32905-2016), 256-bit output.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Sm3;
+
+impl HashMarker for Sm3 {
+    fn size() -> usize {
+        32
+    }
+}
+
+/// SM2 curve (GB/T 32918.5-2017), `curveSM2`.
+///
+/// SM2 signatures hash a user identity digest (`Z_A`) together with the
+/// message; `message_hash` passed to the signing traits is that final SM3
+/// digest.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Sm2;
+
+impl EcdsaCurve for Sm2 {
+    /// TLS `NamedGroup` code point of `curveSM2` (RFC 8998).
+    fn id() -> u32 {
+        41
+    }
+
+    const SCALAR_BYTES: usize = 32;
 }
 
 pub trait EcdsaTypes {
-    type PrivateKey;
+    type PrivateKey: Zeroize;
     type PublicKey;
     type Signature;
     type Curve: EcdsaCurve;
@@ -62,11 +212,55 @@ pub trait EcdsaKeyGen: ErrorType + EcdsaTypes {
     ) -> Result<(Self::PrivateKey, Self::PublicKey), Self::Error>;
 }
 
+/// Key pair returned by [`EcdsaAttestedKeyGen`].
+pub struct AttestedKeyPair<PrivateKey, PublicKey, Attestation> {
+    /// The private key, typically a handle to on-die key material.
+    pub private_key: PrivateKey,
+    pub public_key: PublicKey,
+    /// Signed statement covering `public_key`.
+    pub attestation: Attestation,
+}
+
+/// [`AttestedKeyPair`] of an [`EcdsaAttestedKeyGen`] implementation.
+pub type AttestedKeyPairOf<T> = AttestedKeyPair<
+    <T as EcdsaTypes>::PrivateKey,
+    <T as EcdsaTypes>::PublicKey,
+    <T as EcdsaAttestedKeyGen>::Attestation,
+>;
+
+/// Trait for ECDSA key generation with key attestation.
+///
+/// Implemented by engines that can prove a key pair was generated on-die:
+/// alongside the key pair, an attestation statement binding the public key
+/// to the device is signed with a device identity key, so remote services
+/// can trust where the key was generated.
+pub trait EcdsaAttestedKeyGen: EcdsaKeyGen {
+    /// Handle to the device identity key signing the statement.
+    type IdentityKey;
+    /// Signed statement covering the generated public key.
+    type Attestation;
+
+    /// Generates an ECDSA key pair and its attestation statement.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for key generation.
+    /// - `identity`: The device identity key used to sign the statement.
+    /// - `challenge`: Verifier-supplied nonce included in the statement for freshness.
+    ///
+    /// # Returns
+    /// A result containing the generated key pair and its attestation statement, or an error.
+    fn generate_attested_key_pair(
+        curve: &Self::Curve,
+        identity: &Self::IdentityKey,
+        challenge: &[u8],
+    ) -> Result<AttestedKeyPairOf<Self>, Self::Error>;
+}
+
 /// Trait for ECDSA signing.
 ///
 /// This trait defines the methods required for signing messages using ECDSA.
 pub trait EcdsaSign: ErrorType {
-    type PrivateKey;
+    type PrivateKey: Zeroize;
     type Curve: EcdsaCurve;
     type Signature;
 
@@ -86,6 +280,86 @@ pub trait EcdsaSign: ErrorType {
     ) -> Result<Self::Signature, Self::Error>;
 }
 
+/// Marker for [`EcdsaSign`] implementations that derive nonces
+/// deterministically (RFC 6979).
+///
+/// Signing the same hash with the same key always yields the same signature,
+/// so no entropy source is involved. Secure-boot and manufacturing flows that
+/// must reproduce or audit signatures can require this bound.
+pub trait EcdsaSignDeterministic: EcdsaSign {}
+
+/// Trait for ECDSA signing with a key held in a key vault.
+///
+/// The private key is referenced by [`KeyHandle`] and never leaves the engine.
+pub trait EcdsaSignWithHandle: ErrorType {
+    type PublicKey;
+    type Curve: EcdsaCurve;
+    type Signature;
+
+    /// Returns the public key of a vault-held private key.
+    ///
+    /// # Parameters
+    /// - `key`: Handle of the private key.
+    ///
+    /// # Returns
+    /// A result containing the public key, or an error.
+    fn public_key(&mut self, key: KeyHandle) -> Result<Self::PublicKey, Self::Error>;
+
+    /// Signs a message hash with a vault-held private key.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for signing.
+    /// - `key`: Handle of a private key with `KeyUsage::SIGN`.
+    /// - `message_hash`: The hash of the message to sign.
+    ///
+    /// # Returns
+    /// A result containing the generated signature, or `ErrorKind::InvalidKeyHandle`.
+    fn sign_with_handle<H: HashMarker>(
+        &mut self,
+        curve: &Self::Curve,
+        key: KeyHandle,
+        message_hash: impl AsRef<[u8]>,
+    ) -> Result<Self::Signature, Self::Error>;
+}
+
+/// Trait for streamed ECDSA signing.
+///
+/// This trait is for engines that hash and sign internally: the message is
+/// fed in chunks and the engine produces the signature at the end, so callers
+/// don't need a separate digest engine to pre-compute the message hash.
+pub trait EcdsaSignStream: ErrorType + EcdsaTypes {
+    /// Starts signing a new message.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for signing.
+    /// - `private_key`: The private key to use for signing.
+    ///
+    /// The hash `H` is applied to the streamed message.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error.
+    fn init<H: HashMarker>(
+        &mut self,
+        curve: &Self::Curve,
+        private_key: &Self::PrivateKey,
+    ) -> Result<(), Self::Error>;
+
+    /// Feeds the next part of the message.
+    ///
+    /// # Parameters
+    /// - `data`: The next message chunk.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error.
+    fn update(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Completes the message and produces its signature.
+    ///
+    /// # Returns
+    /// A result containing the generated signature, or an error.
+    fn finalize(&mut self) -> Result<Self::Signature, Self::Error>;
+}
+
 /// Trait for ECDSA verification.
 ///
 /// This trait defines the methods required for verifying ECDSA signatures.
@@ -112,3 +386,110 @@ pub trait EcdsaVerify: ErrorType {
     ) -> Result<(), Self::Error>;
 }
 
+/// Largest digest supported by [`EcdsaSignMessage`] and [`EcdsaVerifyMessage`].
+pub const MAX_MESSAGE_DIGEST_SIZE: usize = 64;
+
+/// Error returned by [`EcdsaSignMessage`] and [`EcdsaVerifyMessage`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HashThenError<EcdsaError, DigestError> {
+    /// The signing or verification engine failed.
+    Ecdsa(EcdsaError),
+    /// The digest engine failed.
+    Digest(DigestError),
+    /// The digest engine's output size does not match the hash `H`.
+    DigestMismatch,
+}
+
+impl<E: Error, D: digest::Error> Error for HashThenError<E, D> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            HashThenError::Ecdsa(e) => e.kind(),
+            HashThenError::Digest(e) => match e.kind() {
+                digest::ErrorKind::Busy => ErrorKind::Busy,
+                digest::ErrorKind::UnsupportedAlgorithm => ErrorKind::UnsupportedAlgorithm,
+                _ => ErrorKind::Other,
+            },
+            HashThenError::DigestMismatch => ErrorKind::UnsupportedAlgorithm,
+        }
+    }
+}
+
+/// Hashes `message` with `digest`, checking its size against `H`.
+fn hash_message<H: HashMarker, D: Digest, E>(
+    digest: &mut D,
+    message: &[u8],
+    out: &mut [u8; MAX_MESSAGE_DIGEST_SIZE],
+) -> Result<usize, HashThenError<E, D::Error>> {
+    if D::OUTPUT_SIZE != H::size() || D::OUTPUT_SIZE > MAX_MESSAGE_DIGEST_SIZE {
+        return Err(HashThenError::DigestMismatch);
+    }
+    digest.reset().map_err(HashThenError::Digest)?;
+    let mut chunk = [0u8; 64];
+    for part in message.chunks(chunk.len()) {
+        let chunk = &mut chunk[..part.len()];
+        chunk.copy_from_slice(part);
+        digest.update(chunk).map_err(HashThenError::Digest)?;
+    }
+    digest
+        .finalize(&mut out[..D::OUTPUT_SIZE])
+        .map_err(HashThenError::Digest)?;
+    Ok(D::OUTPUT_SIZE)
+}
+
+/// Hash-and-sign over any [`EcdsaSign`] implementation.
+///
+/// Hashes the raw message with a digest provider and signs the result, so
+/// callers cannot pair a signature hash `H` with a digest of another size.
+pub trait EcdsaSignMessage: EcdsaSign {
+    /// Hashes `message` with `digest` and signs the result.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for signing.
+    /// - `private_key`: The private key to use for signing.
+    /// - `digest`: Digest provider computing hash `H`.
+    /// - `message`: The raw message.
+    ///
+    /// # Returns
+    /// A result containing the generated signature, or an error.
+    fn sign_message<H: HashMarker, D: Digest>(
+        curve: &Self::Curve,
+        private_key: &Self::PrivateKey,
+        digest: &mut D,
+        message: &[u8],
+    ) -> Result<Self::Signature, HashThenError<Self::Error, D::Error>> {
+        let mut hash = [0u8; MAX_MESSAGE_DIGEST_SIZE];
+        let len = hash_message::<H, D, _>(digest, message, &mut hash)?;
+        Self::sign::<H>(curve, private_key, &hash[..len]).map_err(HashThenError::Ecdsa)
+    }
+}
+
+impl<T: EcdsaSign> EcdsaSignMessage for T {}
+
+/// Hash-and-verify over any [`EcdsaVerify`] implementation.
+pub trait EcdsaVerifyMessage: EcdsaVerify {
+    /// Hashes `message` with `digest` and verifies `signature` over the result.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve to use for verification.
+    /// - `public_key`: The public key to use for verification.
+    /// - `digest`: Digest provider computing hash `H`.
+    /// - `message`: The raw message.
+    /// - `signature`: The signature to verify.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn verify_message<H: HashMarker, D: Digest>(
+        curve: &Self::Curve,
+        public_key: &Self::PublicKey,
+        digest: &mut D,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), HashThenError<Self::Error, D::Error>> {
+        let mut hash = [0u8; MAX_MESSAGE_DIGEST_SIZE];
+        let len = hash_message::<H, D, _>(digest, message, &mut hash)?;
+        Self::verify::<H>(curve, public_key, &hash[..len], signature)
+            .map_err(HashThenError::Ecdsa)
+    }
+}
+
+impl<T: EcdsaVerify> EcdsaVerifyMessage for T {}
@@ -0,0 +1,199 @@
+//! EdDSA signatures (RFC 8032, FIPS 186-5).
+//!
+//! EdDSA is deterministic and hashes the message itself, so unlike ECDSA the
+//! signing traits take the message rather than its digest and need no
+//! per-signature randomness. Keys and signatures are passed in their
+//! standard byte encodings.
+
+use crate::common::Zeroize;
+use crate::contract::ContractVersion;
+
+/// Error kind.
+///
+/// This represents a common set of EdDSA errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// Generating the key pair failed, e.g. due to an entropy failure.
+    KeyGenError,
+
+    /// Producing the signature failed.
+    SigningError,
+
+    /// The signature does not verify.
+    InvalidSignature,
+
+    /// The public key is not a valid encoding of a curve point.
+    InvalidPublicKey,
+
+    /// The variant (context or prehash) is not supported by the implementation.
+    UnsupportedVariant,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Edwards curve marker.
+pub trait EddsaCurve {
+    /// Size of an encoded public key in bytes.
+    const PUBLIC_KEY_SIZE: usize;
+    /// Size of an encoded signature in bytes.
+    const SIGNATURE_SIZE: usize;
+    /// Size of the message digest signed by the prehash variant in bytes.
+    const PREHASH_SIZE: usize;
+}
+
+/// Ed25519, with Ed25519ctx and Ed25519ph (SHA-512 prehash).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Ed25519;
+
+impl EddsaCurve for Ed25519 {
+    const PUBLIC_KEY_SIZE: usize = 32;
+    const SIGNATURE_SIZE: usize = 64;
+    const PREHASH_SIZE: usize = 64;
+}
+
+/// Ed448, with Ed448ph (SHAKE256 prehash).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Ed448;
+
+impl EddsaCurve for Ed448 {
+    const PUBLIC_KEY_SIZE: usize = 57;
+    const SIGNATURE_SIZE: usize = 114;
+    const PREHASH_SIZE: usize = 64;
+}
+
+/// Trait for EdDSA key generation.
+pub trait EddsaKeyGen<C: EddsaCurve>: ErrorType {
+    /// Private key, possibly a handle to key material held by the engine.
+    type PrivateKey: Zeroize;
+
+    /// Generates a key pair.
+    ///
+    /// # Parameters
+    /// - `public_key`: Receives the encoded public key, `C::PUBLIC_KEY_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result containing the private key, or an error.
+    fn generate_key_pair(&mut self, public_key: &mut [u8])
+        -> Result<Self::PrivateKey, Self::Error>;
+}
+
+/// Trait for EdDSA signing.
+pub trait EddsaSign<C: EddsaCurve>: ErrorType {
+    /// Private key, possibly a handle to key material held by the engine.
+    type PrivateKey: Zeroize;
+
+    /// Signs `message` (pure EdDSA).
+    ///
+    /// # Parameters
+    /// - `private_key`: The private key to sign with.
+    /// - `message`: The message itself, not its digest.
+    /// - `context`: Context string, at most 255 bytes. Empty selects plain
+    ///   Ed25519; a non-empty context selects Ed25519ctx.
+    /// - `signature`: Receives the signature, `C::SIGNATURE_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error.
+    fn sign(
+        &mut self,
+        private_key: &Self::PrivateKey,
+        message: &[u8],
+        context: &[u8],
+        signature: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Signs a message digest (Ed25519ph / Ed448ph).
+    ///
+    /// # Parameters
+    /// - `private_key`: The private key to sign with.
+    /// - `message_hash`: SHA-512 (Ed25519ph) or SHAKE256 (Ed448ph) digest of the message, `C::PREHASH_SIZE` bytes.
+    /// - `context`: Context string, at most 255 bytes; empty if unused.
+    /// - `signature`: Receives the signature, `C::SIGNATURE_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result indicating success, or `ErrorKind::UnsupportedVariant`.
+    fn sign_prehashed(
+        &mut self,
+        private_key: &Self::PrivateKey,
+        message_hash: &[u8],
+        context: &[u8],
+        signature: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Trait for EdDSA verification.
+pub trait EddsaVerify<C: EddsaCurve>: ErrorType {
+    /// Verifies a pure EdDSA signature over `message`.
+    ///
+    /// # Parameters
+    /// - `public_key`: Encoded public key, `C::PUBLIC_KEY_SIZE` bytes.
+    /// - `message`: The signed message.
+    /// - `context`: Context string used when signing; empty if unused.
+    /// - `signature`: Encoded signature, `C::SIGNATURE_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn verify(
+        &mut self,
+        public_key: &[u8],
+        message: &[u8],
+        context: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Verifies a prehash EdDSA signature over a message digest.
+    ///
+    /// # Parameters
+    /// - `public_key`: Encoded public key, `C::PUBLIC_KEY_SIZE` bytes.
+    /// - `message_hash`: Digest of the message, `C::PREHASH_SIZE` bytes.
+    /// - `context`: Context string used when signing; empty if unused.
+    /// - `signature`: Encoded signature, `C::SIGNATURE_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn verify_prehashed(
+        &mut self,
+        public_key: &[u8],
+        message_hash: &[u8],
+        context: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Self::Error>;
+}
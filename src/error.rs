@@ -0,0 +1,178 @@
+//! Aggregation of the module error kinds.
+
+use crate::{
+    block_device, cipher, digest, ecdh, ecdsa, eddsa, fault, journal, kdf, key_vault, kv_store,
+    mac, measurement, otp, pq, rng, rsa, x25519,
+};
+
+/// Error kind of any peripheral module.
+///
+/// Lets a workflow spanning several peripherals, e.g. manufacturing
+/// provisioning over digest, OTP and flash, return one error type. Driver
+/// errors are converted through their kind:
+///
+/// ```
+/// use peripheral_traits::{block_device, otp, AnyPeripheralError};
+///
+/// fn provision(
+///     flash: Result<(), block_device::ErrorKind>,
+///     fuses: Result<(), otp::ErrorKind>,
+/// ) -> Result<(), AnyPeripheralError> {
+///     flash?;
+///     fuses?;
+///     Ok(())
+/// }
+///
+/// assert_eq!(
+///     provision(Ok(()), Err(otp::ErrorKind::Busy)),
+///     Err(AnyPeripheralError::Otp(otp::ErrorKind::Busy))
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum AnyPeripheralError {
+    /// Block device error.
+    BlockDevice(block_device::ErrorKind),
+    /// Block cipher error.
+    Cipher(cipher::ErrorKind),
+    /// Digest error.
+    Digest(digest::ErrorKind),
+    /// ECDH error.
+    Ecdh(ecdh::ErrorKind),
+    /// ECDSA error.
+    Ecdsa(ecdsa::ErrorKind),
+    /// EdDSA error.
+    Eddsa(eddsa::ErrorKind),
+    /// Fault recording error.
+    Fault(fault::ErrorKind),
+    /// Journal error.
+    Journal(journal::ErrorKind),
+    /// Key derivation error.
+    Kdf(kdf::ErrorKind),
+    /// Key vault error.
+    KeyVault(key_vault::ErrorKind),
+    /// Key-value store error.
+    KvStore(kv_store::ErrorKind),
+    /// MAC error.
+    Mac(mac::ErrorKind),
+    /// Measurement error.
+    Measurement(measurement::ErrorKind),
+    /// OTP error.
+    Otp(otp::ErrorKind),
+    /// Post-quantum signature error.
+    Pq(pq::ErrorKind),
+    /// Random number generator error.
+    Rng(rng::ErrorKind),
+    /// RSA error.
+    Rsa(rsa::ErrorKind),
+    /// X25519 error.
+    X25519(x25519::ErrorKind),
+}
+
+impl From<block_device::ErrorKind> for AnyPeripheralError {
+    fn from(kind: block_device::ErrorKind) -> Self {
+        AnyPeripheralError::BlockDevice(kind)
+    }
+}
+
+impl From<cipher::ErrorKind> for AnyPeripheralError {
+    fn from(kind: cipher::ErrorKind) -> Self {
+        AnyPeripheralError::Cipher(kind)
+    }
+}
+
+impl From<digest::ErrorKind> for AnyPeripheralError {
+    fn from(kind: digest::ErrorKind) -> Self {
+        AnyPeripheralError::Digest(kind)
+    }
+}
+
+impl From<ecdh::ErrorKind> for AnyPeripheralError {
+    fn from(kind: ecdh::ErrorKind) -> Self {
+        AnyPeripheralError::Ecdh(kind)
+    }
+}
+
+impl From<ecdsa::ErrorKind> for AnyPeripheralError {
+    fn from(kind: ecdsa::ErrorKind) -> Self {
+        AnyPeripheralError::Ecdsa(kind)
+    }
+}
+
+impl From<eddsa::ErrorKind> for AnyPeripheralError {
+    fn from(kind: eddsa::ErrorKind) -> Self {
+        AnyPeripheralError::Eddsa(kind)
+    }
+}
+
+impl From<fault::ErrorKind> for AnyPeripheralError {
+    fn from(kind: fault::ErrorKind) -> Self {
+        AnyPeripheralError::Fault(kind)
+    }
+}
+
+impl From<journal::ErrorKind> for AnyPeripheralError {
+    fn from(kind: journal::ErrorKind) -> Self {
+        AnyPeripheralError::Journal(kind)
+    }
+}
+
+impl From<kdf::ErrorKind> for AnyPeripheralError {
+    fn from(kind: kdf::ErrorKind) -> Self {
+        AnyPeripheralError::Kdf(kind)
+    }
+}
+
+impl From<key_vault::ErrorKind> for AnyPeripheralError {
+    fn from(kind: key_vault::ErrorKind) -> Self {
+        AnyPeripheralError::KeyVault(kind)
+    }
+}
+
+impl From<kv_store::ErrorKind> for AnyPeripheralError {
+    fn from(kind: kv_store::ErrorKind) -> Self {
+        AnyPeripheralError::KvStore(kind)
+    }
+}
+
+impl From<mac::ErrorKind> for AnyPeripheralError {
+    fn from(kind: mac::ErrorKind) -> Self {
+        AnyPeripheralError::Mac(kind)
+    }
+}
+
+impl From<measurement::ErrorKind> for AnyPeripheralError {
+    fn from(kind: measurement::ErrorKind) -> Self {
+        AnyPeripheralError::Measurement(kind)
+    }
+}
+
+impl From<otp::ErrorKind> for AnyPeripheralError {
+    fn from(kind: otp::ErrorKind) -> Self {
+        AnyPeripheralError::Otp(kind)
+    }
+}
+
+impl From<pq::ErrorKind> for AnyPeripheralError {
+    fn from(kind: pq::ErrorKind) -> Self {
+        AnyPeripheralError::Pq(kind)
+    }
+}
+
+impl From<rng::ErrorKind> for AnyPeripheralError {
+    fn from(kind: rng::ErrorKind) -> Self {
+        AnyPeripheralError::Rng(kind)
+    }
+}
+
+impl From<rsa::ErrorKind> for AnyPeripheralError {
+    fn from(kind: rsa::ErrorKind) -> Self {
+        AnyPeripheralError::Rsa(kind)
+    }
+}
+
+impl From<x25519::ErrorKind> for AnyPeripheralError {
+    fn from(kind: x25519::ErrorKind) -> Self {
+        AnyPeripheralError::X25519(kind)
+    }
+}
@@ -0,0 +1,623 @@
+use crate::budget::ResourceBudget;
+use crate::{digest, mac};
+
+/// Provider which served an operation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Path {
+    Primary,
+    Secondary,
+}
+
+/// Error returned by [`WithFallback`], tagged with the provider that raised it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FallbackError<PrimaryError, SecondaryError> {
+    Primary(PrimaryError),
+    Secondary(SecondaryError),
+}
+
+impl<P: digest::Error, S: digest::Error> digest::Error for FallbackError<P, S> {
+    fn kind(&self) -> digest::ErrorKind {
+        match self {
+            FallbackError::Primary(e) => e.kind(),
+            FallbackError::Secondary(e) => e.kind(),
+        }
+    }
+}
+
+impl<P: mac::Error, S: mac::Error> mac::Error for FallbackError<P, S> {
+    fn kind(&self) -> mac::ErrorKind {
+        match self {
+            FallbackError::Primary(e) => e.kind(),
+            FallbackError::Secondary(e) => e.kind(),
+        }
+    }
+}
+
+/// Crypto provider combinator with a software (or second hardware) fallback.
+///
+/// Operations go to `primary` until it fails with `UnsupportedAlgorithm` or
+/// `HardwareFailure` before any input was absorbed; the operation is then
+/// retried on `secondary`, which serves the rest of it. Failures after input
+/// was absorbed are returned as is, since the primary's partial state cannot
+/// be replayed. [`WithFallback::path`] records which provider served the
+/// current operation, e.g. for inclusion in attestation evidence.
+///
+/// A MAC key the primary rejected stays with the secondary: later MAC
+/// operations go to the secondary until the next successful `set_key` on
+/// the primary.
+pub struct WithFallback<Primary, Secondary> {
+    primary: Primary,
+    secondary: Secondary,
+    path: Path,
+    absorbed: bool,
+    key_on_secondary: bool,
+}
+
+impl<Primary, Secondary> WithFallback<Primary, Secondary> {
+    pub fn new(primary: Primary, secondary: Secondary) -> Self {
+        Self {
+            primary,
+            secondary,
+            path: Path::Primary,
+            absorbed: false,
+            key_on_secondary: false,
+        }
+    }
+
+    /// Provider serving the current operation.
+    pub fn path(&self) -> Path {
+        self.path
+    }
+
+    /// Releases both providers.
+    pub fn release(self) -> (Primary, Secondary) {
+        (self.primary, self.secondary)
+    }
+}
+
+impl<P: ResourceBudget, S: ResourceBudget> ResourceBudget for WithFallback<P, S> {
+    // Both providers may hold state for the same operation.
+    const MAX_CONTEXT_SIZE: usize = P::MAX_CONTEXT_SIZE + S::MAX_CONTEXT_SIZE;
+    const MAX_CONCURRENT_OPS: usize = if P::MAX_CONCURRENT_OPS < S::MAX_CONCURRENT_OPS {
+        P::MAX_CONCURRENT_OPS
+    } else {
+        S::MAX_CONCURRENT_OPS
+    };
+    const SCRATCH_BYTES_REQUIRED: usize = P::SCRATCH_BYTES_REQUIRED + S::SCRATCH_BYTES_REQUIRED;
+}
+
+fn digest_fallback(kind: digest::ErrorKind) -> bool {
+    matches!(
+        kind,
+        digest::ErrorKind::UnsupportedAlgorithm | digest::ErrorKind::HardwareFailure
+    )
+}
+
+fn mac_fallback(kind: mac::ErrorKind) -> bool {
+    matches!(
+        kind,
+        mac::ErrorKind::UnsupportedAlgorithm | mac::ErrorKind::HardwareFailure
+    )
+}
+
+impl<P, S> digest::ErrorType for WithFallback<P, S>
+where
+    P: digest::ErrorType,
+    S: digest::ErrorType,
+{
+    type Error = FallbackError<<P as digest::ErrorType>::Error, <S as digest::ErrorType>::Error>;
+}
+
+impl<P, S> digest::Digest for WithFallback<P, S>
+where
+    P: digest::Digest,
+    S: digest::Digest,
+{
+    type InitParams = (P::InitParams, S::InitParams);
+
+    const OUTPUT_SIZE: usize = {
+        assert!(
+            P::OUTPUT_SIZE == S::OUTPUT_SIZE,
+            "providers must produce the same output size"
+        );
+        P::OUTPUT_SIZE
+    };
+
+    const BLOCK_SIZE: usize = {
+        assert!(
+            P::BLOCK_SIZE == S::BLOCK_SIZE,
+            "providers must use the same block size"
+        );
+        P::BLOCK_SIZE
+    };
+
+    fn init(init_params: Self::InitParams) -> Result<(), Self::Error> {
+        use digest::Error as _;
+
+        match P::init(init_params.0) {
+            Err(e) if digest_fallback(e.kind()) => {
+                S::init(init_params.1).map_err(FallbackError::Secondary)
+            }
+            result => result.map_err(FallbackError::Primary),
+        }
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+        use digest::Error as _;
+
+        if self.path == Path::Primary {
+            match self.primary.update(input) {
+                Err(e) if !self.absorbed && digest_fallback(e.kind()) => {
+                    self.path = Path::Secondary;
+                }
+                result => {
+                    self.absorbed = true;
+                    return result.map_err(FallbackError::Primary);
+                }
+            }
+        }
+        self.absorbed = true;
+        self.secondary
+            .update(input)
+            .map_err(FallbackError::Secondary)
+    }
+
+    /// Starts the next operation on the secondary if the primary fails to
+    /// reset.
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        use digest::Error as _;
+
+        self.path = match self.primary.reset() {
+            Ok(()) => Path::Primary,
+            Err(e) if digest_fallback(e.kind()) => Path::Secondary,
+            Err(e) => return Err(FallbackError::Primary(e)),
+        };
+        self.secondary.reset().map_err(FallbackError::Secondary)?;
+        self.absorbed = false;
+        Ok(())
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+        use digest::Error as _;
+
+        if self.path == Path::Primary {
+            match self.primary.finalize(out) {
+                Err(e) if !self.absorbed && digest_fallback(e.kind()) => {
+                    self.path = Path::Secondary;
+                }
+                result => return result.map_err(FallbackError::Primary),
+            }
+        }
+        self.secondary
+            .finalize(out)
+            .map_err(FallbackError::Secondary)
+    }
+}
+
+impl<P, S> mac::ErrorType for WithFallback<P, S>
+where
+    P: mac::ErrorType,
+    S: mac::ErrorType,
+{
+    type Error = FallbackError<<P as mac::ErrorType>::Error, <S as mac::ErrorType>::Error>;
+}
+
+impl<P, S> mac::Mac for WithFallback<P, S>
+where
+    P: mac::Mac,
+    S: mac::Mac,
+{
+    type InitParams = (P::InitParams, S::InitParams);
+
+    const OUTPUT_SIZE: usize = {
+        assert!(
+            P::OUTPUT_SIZE == S::OUTPUT_SIZE,
+            "providers must produce the same output size"
+        );
+        P::OUTPUT_SIZE
+    };
+
+    fn init(init_params: Self::InitParams) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        match P::init(init_params.0) {
+            Err(e) if mac_fallback(e.kind()) => {
+                S::init(init_params.1).map_err(FallbackError::Secondary)
+            }
+            result => result.map_err(FallbackError::Primary),
+        }
+    }
+
+    /// Sets the key on both providers, so either can serve the operation.
+    fn set_key(&mut self, key: &[u8]) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        match self.primary.set_key(key) {
+            Ok(()) => self.key_on_secondary = false,
+            Err(e) if mac_fallback(e.kind()) => self.key_on_secondary = true,
+            Err(e) => return Err(FallbackError::Primary(e)),
+        }
+        self.path = if self.key_on_secondary {
+            Path::Secondary
+        } else {
+            Path::Primary
+        };
+        self.secondary
+            .set_key(key)
+            .map_err(FallbackError::Secondary)
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        if self.path == Path::Primary {
+            match self.primary.update(input) {
+                Err(e) if !self.absorbed && mac_fallback(e.kind()) => {
+                    self.path = Path::Secondary;
+                }
+                result => {
+                    self.absorbed = true;
+                    return result.map_err(FallbackError::Primary);
+                }
+            }
+        }
+        self.absorbed = true;
+        self.secondary
+            .update(input)
+            .map_err(FallbackError::Secondary)
+    }
+
+    /// Keeps the operation on the secondary if it holds the only key, and
+    /// moves it there if the primary fails to reset.
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        self.path = match self.primary.reset() {
+            Ok(()) if !self.key_on_secondary => Path::Primary,
+            Ok(()) => Path::Secondary,
+            Err(e) if mac_fallback(e.kind()) => Path::Secondary,
+            Err(e) => return Err(FallbackError::Primary(e)),
+        };
+        self.secondary.reset().map_err(FallbackError::Secondary)?;
+        self.absorbed = false;
+        Ok(())
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        if self.path == Path::Primary {
+            match self.primary.finalize(out) {
+                Err(e) if !self.absorbed && mac_fallback(e.kind()) => {
+                    self.path = Path::Secondary;
+                }
+                result => return result.map_err(FallbackError::Primary),
+            }
+        }
+        self.secondary
+            .finalize(out)
+            .map_err(FallbackError::Secondary)
+    }
+
+    fn verify(&mut self, tag: &[u8]) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        if self.path == Path::Primary {
+            match self.primary.verify(tag) {
+                Err(e) if !self.absorbed && mac_fallback(e.kind()) => {
+                    self.path = Path::Secondary;
+                }
+                result => return result.map_err(FallbackError::Primary),
+            }
+        }
+        self.secondary.verify(tag).map_err(FallbackError::Secondary)
+    }
+}
+
+impl<P, S> mac::MacVerify for WithFallback<P, S>
+where
+    P: mac::MacVerify,
+    S: mac::MacVerify,
+{
+    fn finalize_and_verify(self, expected: &[u8]) -> Result<(), Self::Error> {
+        use mac::Error as _;
+
+        if self.path == Path::Primary {
+            match self.primary.finalize_and_verify(expected) {
+                Err(e) if !self.absorbed && mac_fallback(e.kind()) => {}
+                result => return result.map_err(FallbackError::Primary),
+            }
+        }
+        self.secondary
+            .finalize_and_verify(expected)
+            .map_err(FallbackError::Secondary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::Digest;
+    use crate::hmac::Hmac;
+    use crate::mac::{Mac, MacVerify};
+    use crate::testutil::Sha256;
+
+    #[derive(Debug)]
+    struct Unsupported;
+
+    impl mac::Error for Unsupported {
+        fn kind(&self) -> mac::ErrorKind {
+            mac::ErrorKind::UnsupportedAlgorithm
+        }
+    }
+
+    /// Engine that rejects keys, or accepts them and then lacks the
+    /// algorithm.
+    struct Engine {
+        accept_key: bool,
+        reset_ok: bool,
+    }
+
+    impl mac::ErrorType for Engine {
+        type Error = Unsupported;
+    }
+
+    impl Mac for Engine {
+        type InitParams = ();
+
+        const OUTPUT_SIZE: usize = 32;
+
+        fn init(_init_params: Self::InitParams) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_key(&mut self, _key: &[u8]) -> Result<(), Self::Error> {
+            self.accept_key.then_some(()).ok_or(Unsupported)
+        }
+
+        fn update(&mut self, _input: &mut [u8]) -> Result<(), Self::Error> {
+            Err(Unsupported)
+        }
+
+        fn reset(&mut self) -> Result<(), Self::Error> {
+            self.reset_ok.then_some(()).ok_or(Unsupported)
+        }
+
+        fn finalize(&mut self, _out: &mut [u8]) -> Result<(), Self::Error> {
+            Err(Unsupported)
+        }
+
+        fn verify(&mut self, _tag: &[u8]) -> Result<(), Self::Error> {
+            Err(Unsupported)
+        }
+    }
+
+    impl MacVerify for Engine {
+        fn finalize_and_verify(self, _expected: &[u8]) -> Result<(), Self::Error> {
+            Err(Unsupported)
+        }
+    }
+
+    type TestMac = WithFallback<Engine, Hmac<Sha256, 64>>;
+
+    fn fallback(accept_key: bool) -> TestMac {
+        WithFallback::new(
+            Engine {
+                accept_key,
+                reset_ok: true,
+            },
+            Hmac::new(Sha256::default()),
+        )
+    }
+
+    fn expected(data: &[u8]) -> [u8; 32] {
+        let mut hmac = Hmac::<Sha256, 64>::new(Sha256::default());
+        let mut tag = [0u8; 32];
+        hmac.set_key(b"key").unwrap();
+        let mut buf = [0u8; 16];
+        buf[..data.len()].copy_from_slice(data);
+        hmac.update(&mut buf[..data.len()]).unwrap();
+        hmac.finalize(&mut tag).unwrap();
+        tag
+    }
+
+    #[test]
+    fn rejected_key_stays_on_secondary_across_reset() {
+        let mut mac = fallback(false);
+        mac.set_key(b"key").unwrap();
+        assert_eq!(mac.path(), Path::Secondary);
+
+        let mut tag = [0u8; 32];
+        mac.update(&mut b"first".clone()).unwrap();
+        mac.reset().unwrap();
+        assert_eq!(mac.path(), Path::Secondary);
+        mac.update(&mut b"second".clone()).unwrap();
+        mac.finalize(&mut tag).unwrap();
+        assert_eq!(tag, expected(b"second"));
+    }
+
+    #[test]
+    fn accepted_key_returns_to_primary() {
+        let mut mac = fallback(false);
+        mac.set_key(b"key").unwrap();
+        mac.primary.accept_key = true;
+        mac.set_key(b"key").unwrap();
+        assert_eq!(mac.path(), Path::Primary);
+    }
+
+    #[test]
+    fn finalize_falls_back_before_any_input() {
+        let mut mac = fallback(true);
+        mac.set_key(b"key").unwrap();
+        let mut tag = [0u8; 32];
+        mac.finalize(&mut tag).unwrap();
+        assert_eq!(mac.path(), Path::Secondary);
+        assert_eq!(tag, expected(b""));
+
+        mac.reset().unwrap();
+        assert_eq!(mac.path(), Path::Primary);
+        assert!(mac.verify(&expected(b"")).is_ok());
+
+        let mut mac = fallback(true);
+        mac.set_key(b"key").unwrap();
+        assert!(mac.finalize_and_verify(&expected(b"")).is_ok());
+    }
+
+    #[test]
+    fn mac_reset_moves_to_secondary_when_primary_fails() {
+        let mut mac = fallback(true);
+        mac.set_key(b"key").unwrap();
+        assert_eq!(mac.path(), Path::Primary);
+        mac.primary.reset_ok = false;
+        mac.reset().unwrap();
+        assert_eq!(mac.path(), Path::Secondary);
+        mac.update(&mut b"second".clone()).unwrap();
+        let mut tag = [0u8; 32];
+        mac.finalize(&mut tag).unwrap();
+        assert_eq!(tag, expected(b"second"));
+    }
+
+    #[derive(Debug)]
+    struct HardwareFailure;
+
+    impl digest::Error for HardwareFailure {
+        fn kind(&self) -> digest::ErrorKind {
+            digest::ErrorKind::HardwareFailure
+        }
+    }
+
+    /// Hash accelerator that fails every call once it is `dead`.
+    #[derive(Default)]
+    struct Accel {
+        hash: Sha256,
+        dead: bool,
+    }
+
+    impl Accel {
+        fn check(&self) -> Result<(), HardwareFailure> {
+            (!self.dead).then_some(()).ok_or(HardwareFailure)
+        }
+    }
+
+    impl digest::ErrorType for Accel {
+        type Error = HardwareFailure;
+    }
+
+    impl Digest for Accel {
+        type InitParams = ();
+
+        const OUTPUT_SIZE: usize = 32;
+
+        const BLOCK_SIZE: usize = 64;
+
+        fn init(_init_params: Self::InitParams) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+            self.check()?;
+            self.hash.update(input).map_err(|e| match e {})
+        }
+
+        fn reset(&mut self) -> Result<(), Self::Error> {
+            self.check()?;
+            self.hash.reset().map_err(|e| match e {})
+        }
+
+        fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+            self.check()?;
+            self.hash.finalize(out).map_err(|e| match e {})
+        }
+    }
+
+    type TestDigest = WithFallback<Accel, Sha256>;
+
+    fn digest(dead: bool) -> TestDigest {
+        WithFallback::new(
+            Accel {
+                dead,
+                ..Default::default()
+            },
+            Sha256::default(),
+        )
+    }
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        let mut hash = Sha256::default();
+        let mut buf = [0u8; 16];
+        buf[..data.len()].copy_from_slice(data);
+        hash.update(&mut buf[..data.len()]).unwrap();
+        let mut out = [0u8; 32];
+        hash.finalize(&mut out).unwrap();
+        out
+    }
+
+    fn finish(digest: &mut TestDigest) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        digest.finalize(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn digest_stays_on_working_primary() {
+        let mut digest = digest(false);
+        digest.update(&mut b"abc".clone()).unwrap();
+        assert_eq!(finish(&mut digest), sha256(b"abc"));
+        assert_eq!(digest.path(), Path::Primary);
+    }
+
+    #[test]
+    fn digest_falls_back_before_any_input() {
+        let mut digest = digest(true);
+        digest.update(&mut b"abc".clone()).unwrap();
+        assert_eq!(digest.path(), Path::Secondary);
+        assert_eq!(finish(&mut digest), sha256(b"abc"));
+
+        let mut digest = self::digest(true);
+        assert_eq!(finish(&mut digest), sha256(b""));
+        assert_eq!(digest.path(), Path::Secondary);
+    }
+
+    #[test]
+    fn digest_failure_after_input_is_returned() {
+        let mut digest = digest(false);
+        digest.update(&mut b"abc".clone()).unwrap();
+        digest.primary.dead = true;
+        assert!(matches!(
+            digest.update(&mut b"def".clone()),
+            Err(FallbackError::Primary(HardwareFailure))
+        ));
+        assert!(matches!(
+            digest.finalize(&mut [0; 32]),
+            Err(FallbackError::Primary(HardwareFailure))
+        ));
+        assert_eq!(digest.path(), Path::Primary);
+    }
+
+    #[test]
+    fn digest_reset_after_primary_hardware_failure_moves_to_secondary() {
+        let mut digest = digest(false);
+        digest.update(&mut b"abc".clone()).unwrap();
+        digest.primary.dead = true;
+        assert!(digest.finalize(&mut [0; 32]).is_err());
+
+        digest.reset().unwrap();
+        assert_eq!(digest.path(), Path::Secondary);
+        digest.update(&mut b"def".clone()).unwrap();
+        assert_eq!(finish(&mut digest), sha256(b"def"));
+
+        digest.primary.dead = false;
+        digest.reset().unwrap();
+        assert_eq!(digest.path(), Path::Primary);
+    }
+
+    #[test]
+    fn hmac_over_a_dead_primary_uses_the_secondary() {
+        let mut hmac = Hmac::<TestDigest, 64>::new(digest(true));
+        let mut tag = [0u8; 32];
+        hmac.set_key(b"key").unwrap();
+        hmac.update(&mut b"data".clone()).unwrap();
+        hmac.finalize(&mut tag).unwrap();
+        assert_eq!(tag, expected(b"data"));
+    }
+}
@@ -0,0 +1,356 @@
+use core::fmt::Write;
+use core::panic::PanicInfo;
+
+use crate::contract::ContractVersion;
+use crate::journal::{self, Journal, RecordId};
+
+/// Error kind.
+///
+/// This represents a common set of fault recording errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The backing storage failed.
+    Storage,
+
+    /// The fault region is full.
+    Full,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Kind of recorded fault.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FaultKind {
+    /// Panic; the data is the UTF-8 location and message.
+    Panic,
+    /// CPU exception; the data is the stacked register frame.
+    Exception,
+    /// Reset caused by the watchdog.
+    WatchdogReset,
+    /// Platform-defined fault code.
+    Other(u16),
+}
+
+impl FaultKind {
+    fn op(self) -> u32 {
+        match self {
+            FaultKind::Panic => OP_FAULT | 1,
+            FaultKind::Exception => OP_FAULT | 2,
+            FaultKind::WatchdogReset => OP_FAULT | 3,
+            FaultKind::Other(code) => OP_PLATFORM_FAULT | code as u32,
+        }
+    }
+
+    fn from_op(op: u32) -> Option<Self> {
+        let code = op as u16;
+        match op & 0xFFFF_0000 {
+            OP_FAULT => Some(match code {
+                1 => FaultKind::Panic,
+                2 => FaultKind::Exception,
+                3 => FaultKind::WatchdogReset,
+                code => FaultKind::Other(code),
+            }),
+            OP_PLATFORM_FAULT => Some(FaultKind::Other(code)),
+            _ => None,
+        }
+    }
+}
+
+/// A fault read back by [`FaultReader`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FaultEntry {
+    /// Identifier to pass to [`FaultReader::acknowledge`].
+    pub id: u32,
+    /// Kind of fault.
+    pub kind: FaultKind,
+    /// Length of the stored fault data.
+    pub len: usize,
+}
+
+/// Records faults into persistent storage.
+///
+/// Recording happens in fault context (panic handler, exception handler,
+/// early boot after a watchdog reset), so implementations must not allocate
+/// and should avoid anything that can itself fault.
+pub trait FaultRecorder: ErrorType {
+    /// Records a fault with its associated data.
+    fn record(&mut self, kind: FaultKind, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Records a panic as `file:line: message`, truncated to fit.
+    fn record_panic(&mut self, info: &PanicInfo<'_>) -> Result<(), Self::Error> {
+        let mut buf = Truncating {
+            buf: [0u8; PANIC_MESSAGE_SIZE],
+            len: 0,
+        };
+        if let Some(location) = info.location() {
+            let _ = write!(buf, "{}:{}: ", location.file(), location.line());
+        }
+        let _ = write!(buf, "{}", info.message());
+        self.record(FaultKind::Panic, &buf.buf[..buf.len])
+    }
+
+    /// Records a CPU exception from its stacked register frame.
+    ///
+    /// Registers are stored little endian in the order given, e.g. R0-R3,
+    /// R12, LR, PC and xPSR for a Cortex-M exception frame.
+    fn record_exception(&mut self, frame: &[u32]) -> Result<(), Self::Error> {
+        let mut buf = [0u8; EXCEPTION_FRAME_WORDS * 4];
+        let words = frame.len().min(EXCEPTION_FRAME_WORDS);
+        for (chunk, word) in buf.chunks_exact_mut(4).zip(&frame[..words]) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        self.record(FaultKind::Exception, &buf[..words * 4])
+    }
+}
+
+/// Reads faults recorded during previous boots.
+pub trait FaultReader: ErrorType {
+    /// Finds the first unacknowledged fault with an identifier of at least `from`.
+    ///
+    /// # Parameters
+    ///
+    /// - `from`: First identifier to consider; `0` starts a scan, `entry.id + 1` continues it.
+    /// - `data`: Buffer receiving the fault data, truncated if too small.
+    fn next_fault(&mut self, from: u32, data: &mut [u8])
+        -> Result<Option<FaultEntry>, Self::Error>;
+
+    /// Marks a fault as handled so later scans skip it.
+    fn acknowledge(&mut self, id: u32) -> Result<(), Self::Error>;
+}
+
+/// Maximum stored length of a panic message.
+pub const PANIC_MESSAGE_SIZE: usize = 128;
+
+/// Maximum number of registers stored for an exception.
+pub const EXCEPTION_FRAME_WORDS: usize = 32;
+
+const OP_FAULT: u32 = 0x4641_0000; // "FA" in the upper half, kind code in the lower
+const OP_PLATFORM_FAULT: u32 = 0x4650_0000; // "FP", with the `FaultKind::Other` code
+
+/// Error returned by [`FaultLog`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultLogError<E>(pub E);
+
+impl<E: journal::Error> Error for FaultLogError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self.0.kind() {
+            journal::ErrorKind::Full => ErrorKind::Full,
+            journal::ErrorKind::Device => ErrorKind::Storage,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Fault log kept in a [`Journal`], e.g. a
+/// [`BlockJournal`](crate::journal::BlockJournal) over a reserved flash region.
+///
+/// Each fault is an intent record; acknowledging it marks the record
+/// complete. Once every fault has been acknowledged the region can be
+/// reclaimed with [`Journal::clear`].
+pub struct FaultLog<J> {
+    journal: J,
+}
+
+impl<J: Journal> FaultLog<J> {
+    pub fn new(journal: J) -> Self {
+        Self { journal }
+    }
+
+    /// Releases the underlying journal.
+    pub fn release(self) -> J {
+        self.journal
+    }
+}
+
+impl<J: Journal> ErrorType for FaultLog<J> {
+    type Error = FaultLogError<J::Error>;
+}
+
+impl<J: Journal> FaultRecorder for FaultLog<J> {
+    fn record(&mut self, kind: FaultKind, data: &[u8]) -> Result<(), Self::Error> {
+        self.journal
+            .append_intent(kind.op(), data)
+            .map(|_| ())
+            .map_err(FaultLogError)
+    }
+}
+
+impl<J: Journal> FaultReader for FaultLog<J> {
+    fn next_fault(
+        &mut self,
+        from: u32,
+        data: &mut [u8],
+    ) -> Result<Option<FaultEntry>, Self::Error> {
+        let mut from: RecordId = from;
+        while let Some(intent) = self
+            .journal
+            .scan_incomplete(from, data)
+            .map_err(FaultLogError)?
+        {
+            if let Some(kind) = FaultKind::from_op(intent.op) {
+                return Ok(Some(FaultEntry {
+                    id: intent.id,
+                    kind,
+                    len: intent.len,
+                }));
+            }
+            from = intent.id + 1;
+        }
+        Ok(None)
+    }
+
+    fn acknowledge(&mut self, id: u32) -> Result<(), Self::Error> {
+        self.journal.mark_complete(id).map_err(FaultLogError)
+    }
+}
+
+/// `fmt::Write` sink that silently drops what does not fit.
+struct Truncating<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Write for Truncating<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut n = s.len().min(N - self.len);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::BlockJournal;
+    use crate::testutil::NorMock;
+    use crate::units::ByteOffset;
+
+    type TestJournal = BlockJournal<NorMock<1024>, 64>;
+
+    fn log() -> FaultLog<TestJournal> {
+        FaultLog::new(journal())
+    }
+
+    /// Eight 64-byte slots.
+    fn journal() -> TestJournal {
+        BlockJournal::new(NorMock::new(256), ByteOffset(256), 512).unwrap()
+    }
+
+    #[test]
+    fn recorded_faults_read_back_until_acknowledged() {
+        let mut log = log();
+        log.record(FaultKind::Panic, b"main.rs:7: boom").unwrap();
+        log.record_exception(&[1, 2, 0xDEAD_BEEF]).unwrap();
+        log.record(FaultKind::WatchdogReset, &[]).unwrap();
+
+        let mut data = [0u8; 16];
+        let entry = log.next_fault(0, &mut data).unwrap().unwrap();
+        assert_eq!((entry.kind, entry.len), (FaultKind::Panic, 15));
+        assert_eq!(&data[..15], b"main.rs:7: boom");
+
+        let entry = log.next_fault(entry.id + 1, &mut data).unwrap().unwrap();
+        assert_eq!((entry.kind, entry.len), (FaultKind::Exception, 12));
+        assert_eq!(data[..12], [1, 0, 0, 0, 2, 0, 0, 0, 0xEF, 0xBE, 0xAD, 0xDE]);
+        log.acknowledge(entry.id).unwrap();
+
+        let entry = log.next_fault(0, &mut data).unwrap().unwrap();
+        assert_eq!(entry.kind, FaultKind::Panic);
+        log.acknowledge(entry.id).unwrap();
+        let entry = log.next_fault(0, &mut data).unwrap().unwrap();
+        assert_eq!((entry.kind, entry.len), (FaultKind::WatchdogReset, 0));
+        log.acknowledge(entry.id).unwrap();
+        assert_eq!(log.next_fault(0, &mut data), Ok(None));
+    }
+
+    #[test]
+    fn platform_codes_do_not_alias_builtin_kinds() {
+        let mut log = log();
+        for code in [1, 3, 0x100, u16::MAX] {
+            log.record(FaultKind::Other(code), &[]).unwrap();
+        }
+
+        let mut from = 0;
+        for code in [1, 3, 0x100, u16::MAX] {
+            let entry = log.next_fault(from, &mut []).unwrap().unwrap();
+            assert_eq!(entry.kind, FaultKind::Other(code));
+            from = entry.id + 1;
+        }
+        assert_eq!(log.next_fault(from, &mut []), Ok(None));
+    }
+
+    #[test]
+    fn other_journal_records_are_skipped() {
+        let mut journal = journal();
+        journal.append_intent(0x1234_0001, &[]).unwrap();
+        let mut log = FaultLog::new(journal);
+        log.record(FaultKind::Panic, b"x").unwrap();
+
+        let entry = log.next_fault(0, &mut []).unwrap().unwrap();
+        assert_eq!((entry.id, entry.kind), (1, FaultKind::Panic));
+    }
+
+    #[test]
+    fn full_log_reports_full() {
+        let mut log = log();
+        for _ in 0..8 {
+            log.record(FaultKind::WatchdogReset, &[]).unwrap();
+        }
+        let err = log.record(FaultKind::WatchdogReset, &[]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Full);
+    }
+
+    #[test]
+    fn truncation_stops_at_char_boundary() {
+        let mut buf = Truncating {
+            buf: [0u8; 5],
+            len: 0,
+        };
+        // 'é' occupies bytes 4..6 and does not fit.
+        write!(buf, "abcdé").unwrap();
+        assert_eq!(&buf.buf[..buf.len], b"abcd");
+        write!(buf, "fg").unwrap();
+        assert_eq!(&buf.buf[..buf.len], b"abcdf");
+        write!(buf, "h").unwrap();
+        assert_eq!(buf.len, 5);
+    }
+}
@@ -0,0 +1,375 @@
+//! HMAC (RFC 2104) over any [`Digest`] provider.
+
+use crate::budget::ResourceBudget;
+use crate::common::Zeroize;
+use crate::digest::{self, Digest, ResumableDigest};
+use crate::mac::{self, Mac, MacVerify, ResumableMac};
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5C;
+
+/// Error returned by [`Hmac`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HmacError<E> {
+    /// The digest provider failed.
+    Digest(E),
+    /// No key has been set.
+    KeyNotSet,
+    /// The output buffer is smaller than the digest output.
+    OutputTooSmall,
+    /// The computed tag does not match the expected one.
+    Mismatch,
+    /// A context passed to `restore_context` is malformed.
+    InvalidContext,
+}
+
+impl<E: digest::Error> mac::Error for HmacError<E> {
+    fn kind(&self) -> mac::ErrorKind {
+        match self {
+            HmacError::Digest(e) => match e.kind() {
+                digest::ErrorKind::InvalidInputLength => mac::ErrorKind::InvalidInputLength,
+                digest::ErrorKind::UnsupportedAlgorithm => mac::ErrorKind::UnsupportedAlgorithm,
+                digest::ErrorKind::MemoryAllocationFailure => {
+                    mac::ErrorKind::MemoryAllocationFailure
+                }
+                digest::ErrorKind::InitializationError => mac::ErrorKind::InitializationError,
+                digest::ErrorKind::UpdateError => mac::ErrorKind::UpdateError,
+                digest::ErrorKind::FinalizationError => mac::ErrorKind::FinalizationError,
+                digest::ErrorKind::Busy => mac::ErrorKind::HardwareAcceleratorBusy,
+                digest::ErrorKind::InvalidOutputSize => mac::ErrorKind::InvalidOutputSize,
+                digest::ErrorKind::PermissionDenied => mac::ErrorKind::PermissionDenied,
+                digest::ErrorKind::NotInitialized => mac::ErrorKind::NotInitialized,
+                _ => mac::ErrorKind::HardwareFailure,
+            },
+            HmacError::KeyNotSet => mac::ErrorKind::NotInitialized,
+            HmacError::OutputTooSmall => mac::ErrorKind::InvalidOutputSize,
+            HmacError::Mismatch => mac::ErrorKind::VerificationFailed,
+            HmacError::InvalidContext => mac::ErrorKind::InvalidInputLength,
+        }
+    }
+}
+
+/// HMAC built from a digest provider with a `BLOCK`-byte input block.
+///
+/// Lets hardware that only exposes a raw hash engine serve where a [`Mac`]
+/// is required. `BLOCK` sizes the key buffers and must equal
+/// `D::BLOCK_SIZE`, which is checked at compile time; the tag is
+/// `D::OUTPUT_SIZE` bytes.
+///
+/// The padded key is cleared when the `Hmac` is dropped or released.
+pub struct Hmac<D, const BLOCK: usize> {
+    digest: D,
+    key: Key<BLOCK>,
+    keyed: bool,
+}
+
+/// Padded HMAC key, cleared on drop.
+struct Key<const BLOCK: usize>([u8; BLOCK]);
+
+impl<const BLOCK: usize> Drop for Key<BLOCK> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<D: Digest, const BLOCK: usize> Hmac<D, BLOCK> {
+    pub fn new(digest: D) -> Self {
+        const {
+            assert!(
+                BLOCK == D::BLOCK_SIZE,
+                "BLOCK must match the digest block size"
+            );
+            assert!(
+                D::OUTPUT_SIZE <= BLOCK,
+                "digest output must fit in one block"
+            )
+        };
+        Self {
+            digest,
+            key: Key([0; BLOCK]),
+            keyed: false,
+        }
+    }
+
+    /// Releases the digest provider. The key is cleared.
+    pub fn release(self) -> D {
+        self.digest
+    }
+
+    /// Restarts the digest and absorbs the inner padded key.
+    fn start(&mut self) -> Result<(), HmacError<D::Error>> {
+        self.digest.reset().map_err(HmacError::Digest)?;
+        self.absorb_padded(IPAD)
+    }
+
+    fn absorb_padded(&mut self, pad: u8) -> Result<(), HmacError<D::Error>> {
+        let mut block = self.key.0;
+        for b in block.iter_mut() {
+            *b ^= pad;
+        }
+        let result = self.digest.update(&mut block).map_err(HmacError::Digest);
+        block.zeroize();
+        result
+    }
+
+    /// Computes the tag into `out[..D::OUTPUT_SIZE]` and restarts for the next message.
+    fn compute(&mut self, out: &mut [u8]) -> Result<(), HmacError<D::Error>> {
+        if !self.keyed {
+            return Err(HmacError::KeyNotSet);
+        }
+        let out = out
+            .get_mut(..D::OUTPUT_SIZE)
+            .ok_or(HmacError::OutputTooSmall)?;
+
+        let mut inner = [0u8; BLOCK];
+        let result = self.outer(&mut inner[..D::OUTPUT_SIZE], out);
+        inner.zeroize();
+        result?;
+        self.start()
+    }
+
+    /// Finishes the inner hash into `inner` and the outer hash into `out`.
+    fn outer(&mut self, inner: &mut [u8], out: &mut [u8]) -> Result<(), HmacError<D::Error>> {
+        self.digest.finalize(inner).map_err(HmacError::Digest)?;
+        self.digest.reset().map_err(HmacError::Digest)?;
+        self.absorb_padded(OPAD)?;
+        self.digest.update(inner).map_err(HmacError::Digest)?;
+        self.digest.finalize(out).map_err(HmacError::Digest)
+    }
+}
+
+impl<D: Digest, const BLOCK: usize> mac::ErrorType for Hmac<D, BLOCK> {
+    type Error = HmacError<D::Error>;
+}
+
+impl<D: ResourceBudget, const BLOCK: usize> ResourceBudget for Hmac<D, BLOCK> {
+    const MAX_CONTEXT_SIZE: usize = D::MAX_CONTEXT_SIZE;
+    const MAX_CONCURRENT_OPS: usize = D::MAX_CONCURRENT_OPS;
+    const SCRATCH_BYTES_REQUIRED: usize = D::SCRATCH_BYTES_REQUIRED;
+}
+
+impl<D: Digest, const BLOCK: usize> Mac for Hmac<D, BLOCK> {
+    type InitParams = D::InitParams;
+
+    const OUTPUT_SIZE: usize = D::OUTPUT_SIZE;
+
+    fn init(init_params: Self::InitParams) -> Result<(), Self::Error> {
+        D::init(init_params).map_err(HmacError::Digest)
+    }
+
+    /// Sets the key; keys longer than `BLOCK` are hashed first.
+    fn set_key(&mut self, key: &[u8]) -> Result<(), Self::Error> {
+        self.key.0.zeroize();
+        self.keyed = false;
+        if key.len() > BLOCK {
+            self.digest.reset().map_err(HmacError::Digest)?;
+            let mut chunk = [0u8; BLOCK];
+            let result = key.chunks(BLOCK).try_for_each(|part| {
+                let chunk = &mut chunk[..part.len()];
+                chunk.copy_from_slice(part);
+                self.digest.update(chunk)
+            });
+            chunk.zeroize();
+            result.map_err(HmacError::Digest)?;
+            self.digest
+                .finalize(&mut self.key.0[..D::OUTPUT_SIZE])
+                .map_err(HmacError::Digest)?;
+        } else {
+            self.key.0[..key.len()].copy_from_slice(key);
+        }
+        self.keyed = true;
+        self.start()
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), Self::Error> {
+        if !self.keyed {
+            return Err(HmacError::KeyNotSet);
+        }
+        self.digest.update(input).map_err(HmacError::Digest)
+    }
+
+    /// Discards the message absorbed so far; the key is kept until
+    /// `set_key`, drop or `release`.
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        if self.keyed {
+            self.start()
+        } else {
+            self.digest.reset().map_err(HmacError::Digest)
+        }
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.compute(out)
+    }
+
+    /// Compares in constant time; a tag of the wrong length never matches.
+    fn verify(&mut self, tag: &[u8]) -> Result<(), Self::Error> {
+        let mut computed = [0u8; BLOCK];
+        self.compute(&mut computed)?;
+        if mac::constant_time_eq(&computed[..D::OUTPUT_SIZE], tag) {
+            Ok(())
+        } else {
+            Err(HmacError::Mismatch)
+        }
+    }
+}
+
+impl<D: Digest, const BLOCK: usize> MacVerify for Hmac<D, BLOCK> {
+    fn finalize_and_verify(mut self, expected: &[u8]) -> Result<(), Self::Error> {
+        self.verify(expected)
+    }
+}
+
+/// The context is the keyed flag and the padded key in plaintext, followed
+/// by the digest state. Callers must zeroize it once restored.
+impl<D: ResumableDigest, const BLOCK: usize> ResumableMac for Hmac<D, BLOCK> {
+    fn save_context(&self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.len() < 1 + BLOCK {
+            return Err(HmacError::OutputTooSmall);
+        }
+        let (head, state) = buf.split_at_mut(1 + BLOCK);
+        head[0] = self.keyed as u8;
+        head[1..].copy_from_slice(&self.key.0);
+        let len = self.digest.export_state(state).map_err(HmacError::Digest)?;
+        Ok(1 + BLOCK + len)
+    }
+
+    fn restore_context(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        if buf.len() < 1 + BLOCK || buf[0] > 1 {
+            return Err(HmacError::InvalidContext);
+        }
+        let (head, state) = buf.split_at(1 + BLOCK);
+        self.digest.import_state(state).map_err(HmacError::Digest)?;
+        self.keyed = head[0] == 1;
+        self.key.0.copy_from_slice(&head[1..]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{hex, Sha256, Sha512};
+
+    /// RFC 4231 test cases 1, 2, 3, 4, 6 and 7 as (key, data, HMAC-SHA-256, HMAC-SHA-512).
+    const CASES: [(&[u8], &[u8], &str, &str); 6] = [
+        (
+            &[0x0b; 20],
+            b"Hi There",
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+             daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+        ),
+        (
+            b"Jefe",
+            b"what do ya want for nothing?",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+        ),
+        (
+            &[0xaa; 20],
+            &[0xdd; 50],
+            "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39\
+             bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+        ),
+        (
+            &[
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+                0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+            ],
+            &[0xcd; 50],
+            "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3db\
+             a91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+        ),
+        (
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+             6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+        ),
+        (
+            &[0xaa; 131],
+            b"This is a test using a larger than block-size key and a larger than block-size \
+              data. The key needs to be hashed before being used by the HMAC algorithm.",
+            "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944\
+             b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+        ),
+    ];
+
+    fn mac<D: Digest + Default, const BLOCK: usize, const N: usize>(
+        key: &[u8],
+        data: &[u8],
+    ) -> [u8; N] {
+        let mut hmac = Hmac::<D, BLOCK>::new(D::default());
+        hmac.set_key(key).unwrap();
+        let mut buf = [0u8; 256];
+        let buf = &mut buf[..data.len()];
+        buf.copy_from_slice(data);
+        hmac.update(buf).unwrap();
+        hmac.finalize_array().unwrap()
+    }
+
+    #[test]
+    fn rfc4231_sha256() {
+        for (key, data, expected, _) in CASES {
+            assert_eq!(mac::<Sha256, 64, 32>(key, data), hex(expected));
+        }
+    }
+
+    #[test]
+    fn rfc4231_sha512() {
+        for (key, data, _, expected) in CASES {
+            assert_eq!(mac::<Sha512, 128, 64>(key, data), hex(expected));
+        }
+    }
+
+    #[test]
+    fn finalize_restarts_with_the_same_key() {
+        let (key, data, expected, _) = CASES[1];
+        let mut hmac = Hmac::<Sha256, 64>::new(Sha256::default());
+        hmac.set_key(key).unwrap();
+        for _ in 0..2 {
+            let mut buf = [0u8; 28];
+            buf.copy_from_slice(data);
+            hmac.update(&mut buf).unwrap();
+            assert_eq!(hmac.finalize_array::<32>().unwrap(), hex(expected));
+        }
+    }
+
+    #[test]
+    fn verify_compares_tags() {
+        let (key, data, expected, _) = CASES[0];
+        let mut hmac = Hmac::<Sha256, 64>::new(Sha256::default());
+        hmac.set_key(key).unwrap();
+        let mut buf = [0u8; 8];
+
+        buf.copy_from_slice(data);
+        hmac.update(&mut buf).unwrap();
+        assert_eq!(hmac.verify(&hex::<32>(expected)), Ok(()));
+
+        let mut wrong = hex::<32>(expected);
+        wrong[31] ^= 1;
+        buf.copy_from_slice(data);
+        hmac.update(&mut buf).unwrap();
+        assert_eq!(hmac.verify(&wrong), Err(HmacError::Mismatch));
+
+        buf.copy_from_slice(data);
+        hmac.update(&mut buf).unwrap();
+        assert_eq!(
+            hmac.verify(&hex::<32>(expected)[..16]),
+            Err(HmacError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn unkeyed_mac_is_rejected() {
+        let mut hmac = Hmac::<Sha256, 64>::new(Sha256::default());
+        assert_eq!(hmac.update(&mut [0]), Err(HmacError::KeyNotSet));
+        assert_eq!(hmac.finalize(&mut [0; 32]), Err(HmacError::KeyNotSet));
+    }
+}
@@ -0,0 +1,322 @@
+//! Hybrid classical + post-quantum signature verification.
+
+use core::marker::PhantomData;
+
+use crate::ecdsa::{EcdsaVerify, HashMarker};
+use crate::pq::{HssVerify, MlDsaParams, MlDsaVerify};
+
+/// Scheme-agnostic signature verification with a bound public key.
+///
+/// Whether `message` is the message itself or its digest is defined by the
+/// implementation; the adapters in this module document which they expect.
+pub trait SignatureVerify {
+    /// Signature representation.
+    type Signature: ?Sized;
+    /// Error type.
+    type Error: core::fmt::Debug;
+
+    /// Verifies `signature` over `message`.
+    fn verify_signature(
+        &mut self,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error>;
+}
+
+/// [`SignatureVerify`] over an [`EcdsaVerify`] implementation.
+///
+/// `message` is the message digest computed with `H`.
+pub struct EcdsaVerifier<V: EcdsaVerify, H> {
+    curve: V::Curve,
+    public_key: V::PublicKey,
+    _hash: PhantomData<H>,
+}
+
+impl<V: EcdsaVerify, H: HashMarker> EcdsaVerifier<V, H> {
+    pub fn new(curve: V::Curve, public_key: V::PublicKey) -> Self {
+        Self {
+            curve,
+            public_key,
+            _hash: PhantomData,
+        }
+    }
+}
+
+impl<V: EcdsaVerify, H: HashMarker> SignatureVerify for EcdsaVerifier<V, H> {
+    type Signature = V::Signature;
+    type Error = V::Error;
+
+    fn verify_signature(
+        &mut self,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error> {
+        V::verify::<H>(&self.curve, &self.public_key, message, signature)
+    }
+}
+
+/// [`SignatureVerify`] over an [`MlDsaVerify`] engine using HashML-DSA.
+///
+/// `message` is the message digest computed with `H`.
+pub struct MlDsaVerifier<'a, V, P, H> {
+    engine: V,
+    public_key: &'a [u8],
+    context: &'a [u8],
+    _params: PhantomData<(P, H)>,
+}
+
+impl<'a, V, P, H> MlDsaVerifier<'a, V, P, H>
+where
+    V: MlDsaVerify<P>,
+    P: MlDsaParams,
+    H: HashMarker,
+{
+    pub fn new(engine: V, public_key: &'a [u8], context: &'a [u8]) -> Self {
+        Self {
+            engine,
+            public_key,
+            context,
+            _params: PhantomData,
+        }
+    }
+}
+
+impl<V, P, H> SignatureVerify for MlDsaVerifier<'_, V, P, H>
+where
+    V: MlDsaVerify<P>,
+    P: MlDsaParams,
+    H: HashMarker,
+{
+    type Signature = [u8];
+    type Error = V::Error;
+
+    fn verify_signature(
+        &mut self,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error> {
+        self.engine
+            .verify_prehashed::<H>(self.public_key, message, self.context, signature)
+    }
+}
+
+/// [`SignatureVerify`] over an [`HssVerify`] engine.
+///
+/// `message` is the signed message itself.
+pub struct HssVerifier<'a, V> {
+    engine: V,
+    public_key: &'a [u8],
+}
+
+impl<'a, V: HssVerify> HssVerifier<'a, V> {
+    pub fn new(engine: V, public_key: &'a [u8]) -> Self {
+        Self { engine, public_key }
+    }
+}
+
+impl<V: HssVerify> SignatureVerify for HssVerifier<'_, V> {
+    type Signature = [u8];
+    type Error = V::Error;
+
+    fn verify_signature(
+        &mut self,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error> {
+        self.engine.verify(self.public_key, message, signature)
+    }
+}
+
+/// How the two verification results are combined.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HybridPolicy {
+    /// Both signatures must verify.
+    RequireBoth,
+    /// At least one signature must verify.
+    RequireEither,
+}
+
+/// Error returned by [`HybridVerifier`].
+///
+/// Each field holds the error of the corresponding verifier, or `None` if
+/// that signature verified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridError<ClassicalError, PqError> {
+    pub classical: Option<ClassicalError>,
+    pub post_quantum: Option<PqError>,
+}
+
+/// Verifies a classical and a post-quantum signature under a policy.
+///
+/// Both signatures are always checked, regardless of the policy, so the
+/// outcome of one does not change which code runs for the other.
+pub struct HybridVerifier<Classical, PostQuantum> {
+    classical: Classical,
+    post_quantum: PostQuantum,
+    policy: HybridPolicy,
+}
+
+impl<C, Q> HybridVerifier<C, Q>
+where
+    C: SignatureVerify,
+    Q: SignatureVerify,
+{
+    pub fn new(classical: C, post_quantum: Q, policy: HybridPolicy) -> Self {
+        Self {
+            classical,
+            post_quantum,
+            policy,
+        }
+    }
+
+    /// Verifies both signatures.
+    ///
+    /// # Parameters
+    /// - `classical_message`, `classical_signature`: Input of the classical verifier.
+    /// - `pq_message`, `pq_signature`: Input of the post-quantum verifier.
+    ///
+    /// The messages differ when the verifiers expect different digests (or
+    /// the raw message); both must be derived from the same signed payload.
+    ///
+    /// # Returns
+    /// `Ok(())` if the results satisfy the policy, or both individual results.
+    pub fn verify(
+        &mut self,
+        classical_message: &[u8],
+        classical_signature: &C::Signature,
+        pq_message: &[u8],
+        pq_signature: &Q::Signature,
+    ) -> Result<(), HybridError<C::Error, Q::Error>> {
+        let classical = self
+            .classical
+            .verify_signature(classical_message, classical_signature)
+            .err();
+        let post_quantum = self
+            .post_quantum
+            .verify_signature(pq_message, pq_signature)
+            .err();
+
+        let accepted = match self.policy {
+            HybridPolicy::RequireBoth => classical.is_none() && post_quantum.is_none(),
+            HybridPolicy::RequireEither => classical.is_none() || post_quantum.is_none(),
+        };
+        if accepted {
+            Ok(())
+        } else {
+            Err(HybridError {
+                classical,
+                post_quantum,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Accepts exactly one signature and counts its calls.
+    struct Stub<E> {
+        valid: &'static [u8],
+        error: E,
+        calls: usize,
+    }
+
+    impl<E: Copy + core::fmt::Debug> SignatureVerify for Stub<E> {
+        type Signature = [u8];
+        type Error = E;
+
+        fn verify_signature(&mut self, message: &[u8], signature: &[u8]) -> Result<(), E> {
+            assert_eq!(message, b"payload");
+            self.calls += 1;
+            if signature == self.valid {
+                Ok(())
+            } else {
+                Err(self.error)
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    struct ClassicalError;
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    struct PqError(u8);
+
+    type Verifier = HybridVerifier<Stub<ClassicalError>, Stub<PqError>>;
+
+    fn verifier(policy: HybridPolicy) -> Verifier {
+        HybridVerifier::new(
+            Stub {
+                valid: b"ecdsa",
+                error: ClassicalError,
+                calls: 0,
+            },
+            Stub {
+                valid: b"ml-dsa",
+                error: PqError(7),
+                calls: 0,
+            },
+            policy,
+        )
+    }
+
+    fn check(
+        policy: HybridPolicy,
+        classical_ok: bool,
+        pq_ok: bool,
+    ) -> Result<(), HybridError<ClassicalError, PqError>> {
+        let mut verifier = verifier(policy);
+        let classical: &[u8] = if classical_ok { b"ecdsa" } else { b"forged" };
+        let pq: &[u8] = if pq_ok { b"ml-dsa" } else { b"forged" };
+        let result = verifier.verify(b"payload", classical, b"payload", pq);
+        // Neither result short-circuits the other check.
+        assert_eq!(
+            (verifier.classical.calls, verifier.post_quantum.calls),
+            (1, 1)
+        );
+        result
+    }
+
+    #[test]
+    fn require_both() {
+        use HybridPolicy::RequireBoth;
+        assert_eq!(check(RequireBoth, true, true), Ok(()));
+        assert_eq!(
+            check(RequireBoth, true, false),
+            Err(HybridError {
+                classical: None,
+                post_quantum: Some(PqError(7)),
+            })
+        );
+        assert_eq!(
+            check(RequireBoth, false, true),
+            Err(HybridError {
+                classical: Some(ClassicalError),
+                post_quantum: None,
+            })
+        );
+        assert_eq!(
+            check(RequireBoth, false, false),
+            Err(HybridError {
+                classical: Some(ClassicalError),
+                post_quantum: Some(PqError(7)),
+            })
+        );
+    }
+
+    #[test]
+    fn require_either() {
+        use HybridPolicy::RequireEither;
+        assert_eq!(check(RequireEither, true, true), Ok(()));
+        assert_eq!(check(RequireEither, true, false), Ok(()));
+        assert_eq!(check(RequireEither, false, true), Ok(()));
+        assert_eq!(
+            check(RequireEither, false, false),
+            Err(HybridError {
+                classical: Some(ClassicalError),
+                post_quantum: Some(PqError(7)),
+            })
+        );
+    }
+}
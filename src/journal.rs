@@ -0,0 +1,548 @@
+use crate::block_device::BlockDevice;
+use crate::contract::ContractVersion;
+use crate::range_check::check_aligned;
+use crate::units::ByteOffset;
+
+/// Error kind.
+///
+/// This represents a common set of journal operation errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The underlying storage reported an error.
+    Device,
+
+    /// There is no room left in the journal for another record.
+    Full,
+
+    /// The record payload does not fit in a journal slot.
+    PayloadTooLarge,
+
+    /// The referenced record does not exist or was not written.
+    InvalidRecord,
+
+    /// The journal geometry does not match the underlying storage.
+    InvalidGeometry,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by journal implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Identifier of a record within a journal.
+pub type RecordId = u32;
+
+/// An intent record which has not been marked complete.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Intent {
+    /// Identifier to pass to [`Journal::mark_complete`].
+    pub id: RecordId,
+    /// Caller-defined operation code.
+    pub op: u32,
+    /// Length of the payload stored with the record.
+    pub len: usize,
+}
+
+/// Persistent write-ahead journal.
+///
+/// Callers append an intent record before starting a multi-step operation
+/// (e.g. a fuse-programming sequence) and mark it complete once every step
+/// succeeded. After a reset, any record still incomplete identifies an
+/// interrupted operation which must be resumed or flagged.
+pub trait Journal: ErrorType {
+    /// Persists an intent record.
+    ///
+    /// # Parameters
+    ///
+    /// - `op`: Caller-defined operation code.
+    /// - `payload`: Data needed to resume or diagnose the operation.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the newly written record.
+    fn append_intent(&mut self, op: u32, payload: &[u8]) -> Result<RecordId, Self::Error>;
+
+    /// Marks a previously appended record as complete.
+    fn mark_complete(&mut self, id: RecordId) -> Result<(), Self::Error>;
+
+    /// Finds the first incomplete record with an identifier of at least `from`.
+    ///
+    /// # Parameters
+    ///
+    /// - `from`: First record identifier to consider. Pass `0` to start a scan,
+    ///   and `intent.id + 1` to continue it.
+    /// - `payload`: Buffer receiving the record payload. Payload bytes which do
+    ///   not fit are dropped; `Intent::len` always reports the stored length.
+    ///
+    /// # Returns
+    ///
+    /// The next incomplete record, or `None` when the scan is finished.
+    fn scan_incomplete(
+        &mut self,
+        from: RecordId,
+        payload: &mut [u8],
+    ) -> Result<Option<Intent>, Self::Error>;
+
+    /// Discards every record, completed or not.
+    fn clear(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Error returned by [`BlockJournal`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockJournalError<E> {
+    /// Underlying block device error
+    Device(E),
+    /// Journal region exhausted
+    Full,
+    /// Payload larger than a slot
+    PayloadTooLarge,
+    /// Record identifier out of range or never written
+    InvalidRecord,
+    /// Slot size or region not aligned to the device geometry
+    InvalidGeometry,
+}
+
+impl<E: core::fmt::Debug> Error for BlockJournalError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            BlockJournalError::Device(_) => ErrorKind::Device,
+            BlockJournalError::Full => ErrorKind::Full,
+            BlockJournalError::PayloadTooLarge => ErrorKind::PayloadTooLarge,
+            BlockJournalError::InvalidRecord => ErrorKind::InvalidRecord,
+            BlockJournalError::InvalidGeometry => ErrorKind::InvalidGeometry,
+        }
+    }
+}
+
+const MAGIC: u32 = 0x4C4E_524A; // "JRNL"
+const HEADER_SIZE: usize = 16;
+
+/// [`Journal`] stored in a region of a NOR-like [`BlockDevice`].
+///
+/// The region is split into `SLOT` byte slots, one per record. The last
+/// program unit of each slot is the completion marker: it stays erased while
+/// the record is pending and is programmed to zero when it completes, so no
+/// slot is ever programmed twice in the same place.
+///
+/// Slot layout:
+///
+/// | Bytes     | Content                                  |
+/// |-----------|------------------------------------------|
+/// | 0..4      | magic, little endian                     |
+/// | 4..8      | operation code, little endian            |
+/// | 8..12     | payload length, little endian            |
+/// | 12..16    | FNV-1a of bytes 4..12 and the payload    |
+/// | 16..      | payload                                  |
+/// | last unit | completion marker                        |
+pub struct BlockJournal<D, const SLOT: usize> {
+    device: D,
+    base: usize,
+    slots: u32,
+    next: u32,
+}
+
+impl<D, const SLOT: usize> ErrorType for BlockJournal<D, SLOT>
+where
+    D: BlockDevice,
+{
+    type Error = BlockJournalError<D::Error>;
+}
+
+impl<D, const SLOT: usize> BlockJournal<D, SLOT>
+where
+    D: BlockDevice,
+{
+    /// Opens the journal stored in `size` bytes at `base`.
+    ///
+    /// The region must have been erased (see [`Journal::clear`]) before first
+    /// use. Existing records are scanned to find the append position.
+    ///
+    /// The device must support partial-page and bit-clearing programs.
+    pub fn new(
+        device: D,
+        base: ByteOffset,
+        size: usize,
+    ) -> Result<Self, BlockJournalError<D::Error>> {
+        let base = base.get();
+        let unit = device.program_size();
+        let caps = device.program_capabilities();
+        // Completion markers are programmed into already programmed slots.
+        if !caps.partial_page
+            || !caps.bit_clearing
+            || unit == 0
+            || !SLOT.is_multiple_of(unit)
+            || SLOT < HEADER_SIZE + unit
+            || check_aligned(base, size, device.erase_size(), device.capacity()).is_err()
+        {
+            return Err(BlockJournalError::InvalidGeometry);
+        }
+
+        let mut journal = Self {
+            device,
+            base,
+            slots: (size / SLOT) as u32,
+            next: 0,
+        };
+        // Torn slots are skipped as well, since NOR cells can't be reprogrammed.
+        while journal.next < journal.slots && journal.slot_used(journal.next)? {
+            journal.next += 1;
+        }
+        Ok(journal)
+    }
+
+    /// Releases the underlying device.
+    pub fn release(self) -> D {
+        self.device
+    }
+
+    /// Largest payload a single record can hold.
+    pub fn max_payload(&self) -> usize {
+        SLOT - HEADER_SIZE - self.device.program_size()
+    }
+
+    fn slot_addr(&self, id: RecordId) -> usize {
+        self.base + id as usize * SLOT
+    }
+
+    fn marker_addr(&self, id: RecordId) -> usize {
+        self.slot_addr(id) + SLOT - self.device.program_size()
+    }
+
+    fn slot_used(&mut self, id: RecordId) -> Result<bool, BlockJournalError<D::Error>> {
+        let mut header = [0u8; HEADER_SIZE];
+        self.device
+            .read(ByteOffset(self.slot_addr(id)), &mut header)
+            .map_err(BlockJournalError::Device)?;
+        Ok(header.iter().any(|&b| b != 0xFF))
+    }
+
+    /// Reads the header of slot `id`, returning `(op, len)` for valid records.
+    fn read_header(
+        &mut self,
+        id: RecordId,
+    ) -> Result<Option<(u32, usize)>, BlockJournalError<D::Error>> {
+        let mut header = [0u8; HEADER_SIZE];
+        self.device
+            .read(ByteOffset(self.slot_addr(id)), &mut header)
+            .map_err(BlockJournalError::Device)?;
+
+        if word(&header, 0) != MAGIC {
+            return Ok(None);
+        }
+        let op = word(&header, 4);
+        let len = word(&header, 8) as usize;
+        if len > self.max_payload() {
+            return Ok(None);
+        }
+
+        let mut hash = fnv1a(FNV_OFFSET, &header[4..12]);
+        let mut chunk = [0u8; 32];
+        let mut done = 0;
+        while done < len {
+            let n = (len - done).min(chunk.len());
+            self.device
+                .read(
+                    ByteOffset(self.slot_addr(id) + HEADER_SIZE + done),
+                    &mut chunk[..n],
+                )
+                .map_err(BlockJournalError::Device)?;
+            hash = fnv1a(hash, &chunk[..n]);
+            done += n;
+        }
+
+        // A torn write leaves a header whose checksum does not match.
+        Ok((hash == word(&header, 12)).then_some((op, len)))
+    }
+
+    fn is_complete(&mut self, id: RecordId) -> Result<bool, BlockJournalError<D::Error>> {
+        let mut marker = [0u8; 1];
+        self.device
+            .read(ByteOffset(self.marker_addr(id)), &mut marker)
+            .map_err(BlockJournalError::Device)?;
+        Ok(marker[0] != 0xFF)
+    }
+}
+
+impl<D, const SLOT: usize> Journal for BlockJournal<D, SLOT>
+where
+    D: BlockDevice,
+{
+    fn append_intent(&mut self, op: u32, payload: &[u8]) -> Result<RecordId, Self::Error> {
+        if payload.len() > self.max_payload() {
+            return Err(BlockJournalError::PayloadTooLarge);
+        }
+        if self.next >= self.slots {
+            return Err(BlockJournalError::Full);
+        }
+
+        let body_len = SLOT - self.device.program_size();
+        let mut slot = [0xFFu8; SLOT];
+        slot[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        slot[4..8].copy_from_slice(&op.to_le_bytes());
+        slot[8..12].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+        slot[HEADER_SIZE..HEADER_SIZE + payload.len()].copy_from_slice(payload);
+        let hash = fnv1a(fnv1a(FNV_OFFSET, &slot[4..12]), payload);
+        slot[12..16].copy_from_slice(&hash.to_le_bytes());
+
+        let id = self.next;
+        self.device
+            .program(ByteOffset(self.slot_addr(id)), &slot[..body_len])
+            .map_err(BlockJournalError::Device)?;
+        self.next += 1;
+        Ok(id)
+    }
+
+    fn mark_complete(&mut self, id: RecordId) -> Result<(), Self::Error> {
+        if id >= self.next {
+            return Err(BlockJournalError::InvalidRecord);
+        }
+        if self.is_complete(id)? {
+            return Ok(());
+        }
+
+        let zeros = [0u8; SLOT];
+        let unit = self.device.program_size();
+        self.device
+            .program(ByteOffset(self.marker_addr(id)), &zeros[..unit])
+            .map_err(BlockJournalError::Device)
+    }
+
+    fn scan_incomplete(
+        &mut self,
+        from: RecordId,
+        payload: &mut [u8],
+    ) -> Result<Option<Intent>, Self::Error> {
+        for id in from..self.next {
+            let Some((op, len)) = self.read_header(id)? else {
+                continue;
+            };
+            if self.is_complete(id)? {
+                continue;
+            }
+
+            let n = len.min(payload.len());
+            self.device
+                .read(
+                    ByteOffset(self.slot_addr(id) + HEADER_SIZE),
+                    &mut payload[..n],
+                )
+                .map_err(BlockJournalError::Device)?;
+            return Ok(Some(Intent { id, op, len }));
+        }
+        Ok(None)
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        let size = self.slots as usize * SLOT;
+        let size = size.next_multiple_of(self.device.erase_size());
+        self.device
+            .erase(ByteOffset(self.base), size)
+            .map_err(BlockJournalError::Device)?;
+        self.next = 0;
+        Ok(())
+    }
+}
+
+pub(crate) const FNV_OFFSET: u32 = 0x811C_9DC5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+pub(crate) fn fnv1a(mut hash: u32, data: &[u8]) -> u32 {
+    for &b in data {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+pub(crate) fn word(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_device::ProgramCapabilities;
+    use crate::testutil::NorMock;
+
+    /// Eight 64-byte slots in the second and third 256-byte sectors.
+    type TestJournal = BlockJournal<NorMock<1024>, 64>;
+
+    fn open(device: NorMock<1024>) -> TestJournal {
+        BlockJournal::new(device, ByteOffset(256), 512).unwrap()
+    }
+
+    fn scan_all(journal: &mut TestJournal) -> [Option<(RecordId, u32, u8)>; 8] {
+        let mut found = [None; 8];
+        let mut from = 0;
+        let mut payload = [0u8; 4];
+        for slot in found.iter_mut() {
+            let Some(intent) = journal.scan_incomplete(from, &mut payload).unwrap() else {
+                break;
+            };
+            *slot = Some((intent.id, intent.op, payload[0]));
+            from = intent.id + 1;
+        }
+        found
+    }
+
+    #[test]
+    fn scan_returns_incomplete_records_in_order() {
+        let mut journal = open(NorMock::new(256));
+        for op in 0..4 {
+            assert_eq!(journal.append_intent(op, &[op as u8 + 10]), Ok(op));
+        }
+        journal.mark_complete(1).unwrap();
+        journal.mark_complete(1).unwrap();
+
+        assert_eq!(
+            scan_all(&mut journal)[..4],
+            [Some((0, 0, 10)), Some((2, 2, 12)), Some((3, 3, 13)), None]
+        );
+        let mut payload = [0u8; 4];
+        assert_eq!(
+            journal
+                .scan_incomplete(3, &mut payload)
+                .unwrap()
+                .map(|i| i.id),
+            Some(3)
+        );
+        assert_eq!(journal.scan_incomplete(4, &mut payload), Ok(None));
+    }
+
+    #[test]
+    fn reopening_resumes_after_the_last_record() {
+        let mut journal = open(NorMock::new(256));
+        journal.append_intent(7, &[1]).unwrap();
+        journal.append_intent(8, &[2]).unwrap();
+        journal.mark_complete(0).unwrap();
+
+        let mut journal = open(journal.release());
+        assert_eq!(scan_all(&mut journal)[..2], [Some((1, 8, 2)), None]);
+        assert_eq!(journal.append_intent(9, &[3]), Ok(2));
+    }
+
+    #[test]
+    fn torn_record_is_skipped_and_not_reused() {
+        let mut journal = open(NorMock::new(256));
+        journal.append_intent(1, &[1]).unwrap();
+
+        let mut device = journal.release();
+        device.cut_power_after(10);
+        let mut journal = open(device);
+        assert!(matches!(
+            journal.append_intent(2, &[2]),
+            Err(BlockJournalError::Device(_))
+        ));
+
+        let mut device = journal.release();
+        device.power_on();
+        let mut journal = open(device);
+        assert_eq!(scan_all(&mut journal)[..2], [Some((0, 1, 1)), None]);
+        assert_eq!(journal.append_intent(3, &[3]), Ok(2));
+        assert_eq!(
+            scan_all(&mut journal)[..3],
+            [Some((0, 1, 1)), Some((2, 3, 3)), None]
+        );
+    }
+
+    #[test]
+    fn full_journal_is_reusable_after_clear() {
+        let mut device = NorMock::new(256);
+        device.data[..4].copy_from_slice(b"keep");
+        let mut journal = open(device);
+        for op in 0..8 {
+            journal.append_intent(op, &[]).unwrap();
+        }
+        assert_eq!(journal.append_intent(8, &[]), Err(BlockJournalError::Full));
+
+        journal.clear().unwrap();
+        assert_eq!(scan_all(&mut journal)[0], None);
+        assert_eq!(journal.append_intent(9, &[9]), Ok(0));
+        // Clearing stays within the journal region.
+        assert_eq!(&journal.release().data[..4], b"keep");
+    }
+
+    #[test]
+    fn payload_is_truncated_to_the_buffer() {
+        let mut journal = open(NorMock::new(256));
+        journal.append_intent(1, &[1, 2, 3, 4, 5, 6]).unwrap();
+        let mut payload = [0u8; 4];
+        let intent = journal.scan_incomplete(0, &mut payload).unwrap().unwrap();
+        assert_eq!(intent.len, 6);
+        assert_eq!(payload, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn invalid_requests_are_rejected() {
+        let mut journal = open(NorMock::new(256));
+        assert_eq!(journal.max_payload(), 64 - HEADER_SIZE - 1);
+        assert_eq!(
+            journal.append_intent(1, &[0; 48]),
+            Err(BlockJournalError::PayloadTooLarge)
+        );
+        assert_eq!(
+            journal.mark_complete(0),
+            Err(BlockJournalError::InvalidRecord)
+        );
+        assert!(matches!(
+            BlockJournal::<_, 64>::new(NorMock::<1024>::new(256), ByteOffset(128), 512),
+            Err(BlockJournalError::InvalidGeometry)
+        ));
+        assert!(matches!(
+            BlockJournal::<_, 8>::new(NorMock::<1024>::new(256), ByteOffset(0), 256),
+            Err(BlockJournalError::InvalidGeometry)
+        ));
+    }
+
+    #[test]
+    fn devices_without_nor_semantics_are_rejected() {
+        for caps in [
+            ProgramCapabilities::default(),
+            ProgramCapabilities {
+                partial_page: true,
+                ..Default::default()
+            },
+            ProgramCapabilities {
+                bit_clearing: true,
+                ..Default::default()
+            },
+        ] {
+            let mut device = NorMock::new(256);
+            device.caps = caps;
+            assert!(matches!(
+                TestJournal::new(device, ByteOffset(256), 512),
+                Err(BlockJournalError::InvalidGeometry)
+            ));
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! Key derivation functions: HKDF (RFC 5869), the NIST SP 800-108 counter
+//! mode KDF and PBKDF2 (RFC 8018).
+
+use crate::common::{ConstantTimeEq, Zeroize};
+use crate::contract::ContractVersion;
+use crate::ecdsa::HashMarker;
+use crate::mac::{self, Mac, MacAlgorithm};
+
+/// Error kind.
+///
+/// This represents a common set of key derivation errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// An input (key, salt, label or context) has an invalid length.
+    InvalidInputLength,
+
+    /// The requested output length exceeds what the KDF can derive.
+    InvalidOutputLength,
+
+    /// The hash or PRF is not supported by the implementation.
+    UnsupportedAlgorithm,
+
+    /// General hardware failure.
+    HardwareFailure,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+pub trait KdfTypes {
+    /// Key derivation key: raw bytes, or a handle to a key held by the engine.
+    type Key: Zeroize;
+}
+
+/// Trait for the HKDF extract step.
+pub trait KdfExtract<H: HashMarker>: ErrorType + KdfTypes {
+    /// Extracts a pseudorandom key from input keying material.
+    ///
+    /// # Parameters
+    /// - `salt`: Optional salt; empty selects a string of `H::size()` zeros.
+    /// - `ikm`: Input keying material, e.g. an ECDH shared secret.
+    ///
+    /// # Returns
+    /// A result containing the pseudorandom key, or an error.
+    fn extract(&mut self, salt: &[u8], ikm: &[u8]) -> Result<Self::Key, Self::Error>;
+}
+
+/// Trait for the HKDF expand step.
+pub trait KdfExpand<H: HashMarker>: ErrorType + KdfTypes {
+    /// Expands a pseudorandom key into output keying material.
+    ///
+    /// # Parameters
+    /// - `prk`: Pseudorandom key, e.g. from [`KdfExtract::extract`].
+    /// - `info`: Context and application specific information.
+    /// - `okm`: Receives the output keying material, at most `255 * H::size()` bytes.
+    ///
+    /// # Returns
+    /// A result indicating success, or `ErrorKind::InvalidOutputLength`.
+    fn expand(&mut self, prk: &Self::Key, info: &[u8], okm: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+/// Trait for the NIST SP 800-108 KDF in counter mode with PRF `P`.
+///
+/// The fixed input is `[i]_32 || label || 0x00 || context || [L]_32`, with a
+/// 32-bit big-endian counter `i` and output length `L` in bits.
+pub trait Sp800_108Kdf<P: MacAlgorithm>: ErrorType + KdfTypes {
+    /// Derives key material.
+    ///
+    /// # Parameters
+    /// - `key`: The key derivation key.
+    /// - `label`: Purpose of the derived key.
+    /// - `context`: Information binding the derived key to its use, e.g. a transcript hash.
+    /// - `okm`: Receives the derived key material.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error.
+    fn derive(
+        &mut self,
+        key: &Self::Key,
+        label: &[u8],
+        context: &[u8],
+        okm: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Trait for PBKDF2 (RFC 8018) with HMAC over hash `H`.
+pub trait Pbkdf2<H: HashMarker>: ErrorType {
+    /// Derives key material from a password.
+    ///
+    /// # Parameters
+    /// - `password`: The password.
+    /// - `salt`: The salt.
+    /// - `iterations`: Iteration count, at least 1.
+    /// - `okm`: Receives the derived key material.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error.
+    fn derive(
+        &mut self,
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        okm: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Largest hash output supported by [`Hkdf`].
+pub const HKDF_MAX_HASH_SIZE: usize = 64;
+
+/// Pseudorandom key produced by [`Hkdf`].
+#[derive(Clone)]
+pub struct HkdfPrk {
+    bytes: [u8; HKDF_MAX_HASH_SIZE],
+    len: usize,
+}
+
+impl HkdfPrk {
+    /// Wraps a pseudorandom key obtained elsewhere, e.g. a TLS 1.3 secret.
+    ///
+    /// Returns `None` if `prk` is longer than [`HKDF_MAX_HASH_SIZE`].
+    pub fn from_bytes(prk: &[u8]) -> Option<Self> {
+        let mut bytes = [0; HKDF_MAX_HASH_SIZE];
+        bytes.get_mut(..prk.len())?.copy_from_slice(prk);
+        Some(Self {
+            bytes,
+            len: prk.len(),
+        })
+    }
+}
+
+impl AsRef<[u8]> for HkdfPrk {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl ConstantTimeEq for HkdfPrk {
+    fn ct_eq(&self, other: &Self) -> bool {
+        self.as_ref().ct_eq(other.as_ref())
+    }
+}
+
+impl Zeroize for HkdfPrk {
+    fn zeroize(&mut self) {
+        self.bytes.zeroize();
+        self.len = 0;
+    }
+}
+
+impl Drop for HkdfPrk {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Error returned by [`Hkdf`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HkdfError<E> {
+    /// The MAC provider failed.
+    Mac(E),
+    /// The hash size does not match the MAC, or exceeds [`HKDF_MAX_HASH_SIZE`].
+    UnsupportedHash,
+    /// More than `255 * H::size()` bytes were requested.
+    OutputTooLong,
+}
+
+impl<E: mac::Error> Error for HkdfError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            HkdfError::Mac(e) => match e.kind() {
+                mac::ErrorKind::HardwareAcceleratorBusy => ErrorKind::Busy,
+                mac::ErrorKind::InvalidInputLength => ErrorKind::InvalidInputLength,
+                mac::ErrorKind::UnsupportedAlgorithm => ErrorKind::UnsupportedAlgorithm,
+                mac::ErrorKind::HardwareFailure => ErrorKind::HardwareFailure,
+                _ => ErrorKind::Other,
+            },
+            HkdfError::UnsupportedHash => ErrorKind::UnsupportedAlgorithm,
+            HkdfError::OutputTooLong => ErrorKind::InvalidOutputLength,
+        }
+    }
+}
+
+/// Software HKDF over an HMAC provider, e.g. [`Hmac`](crate::hmac::Hmac).
+///
+/// The hash marker `H` must match the HMAC's hash.
+pub struct Hkdf<M> {
+    mac: M,
+}
+
+impl<M: Mac> Hkdf<M> {
+    pub fn new(mac: M) -> Self {
+        Self { mac }
+    }
+
+    /// Releases the MAC provider.
+    pub fn release(self) -> M {
+        self.mac
+    }
+
+    fn check_hash<H: HashMarker>() -> Result<(), HkdfError<M::Error>> {
+        if H::size() == M::OUTPUT_SIZE && M::OUTPUT_SIZE <= HKDF_MAX_HASH_SIZE {
+            Ok(())
+        } else {
+            Err(HkdfError::UnsupportedHash)
+        }
+    }
+
+    /// Feeds `data` to the MAC through a scratch copy, as `Mac::update` takes a mutable slice.
+    fn absorb(&mut self, data: &[u8]) -> Result<(), HkdfError<M::Error>> {
+        let mut chunk = [0u8; HKDF_MAX_HASH_SIZE];
+        let result = data.chunks(HKDF_MAX_HASH_SIZE).try_for_each(|part| {
+            let chunk = &mut chunk[..part.len()];
+            chunk.copy_from_slice(part);
+            self.mac.update(chunk)
+        });
+        // The IKM and the previous output block pass through here.
+        chunk.zeroize();
+        result.map_err(HkdfError::Mac)
+    }
+
+    /// Computes the output blocks of RFC 5869 section 2.3 into `okm`, using
+    /// `t` for the previous block.
+    fn expand_blocks(
+        &mut self,
+        prk: &HkdfPrk,
+        info: &[u8],
+        okm: &mut [u8],
+        t: &mut [u8],
+    ) -> Result<(), HkdfError<M::Error>> {
+        for (i, out) in okm.chunks_mut(M::OUTPUT_SIZE).enumerate() {
+            self.mac.set_key(prk.as_ref()).map_err(HkdfError::Mac)?;
+            if i > 0 {
+                self.mac.update(t).map_err(HkdfError::Mac)?;
+            }
+            self.absorb(info)?;
+            self.mac
+                .update(&mut [i as u8 + 1])
+                .map_err(HkdfError::Mac)?;
+            self.mac.finalize(t).map_err(HkdfError::Mac)?;
+            out.copy_from_slice(&t[..out.len()]);
+        }
+        Ok(())
+    }
+}
+
+impl<M: Mac> ErrorType for Hkdf<M> {
+    type Error = HkdfError<M::Error>;
+}
+
+impl<M> KdfTypes for Hkdf<M> {
+    type Key = HkdfPrk;
+}
+
+impl<H: HashMarker, M: Mac> KdfExtract<H> for Hkdf<M> {
+    fn extract(&mut self, salt: &[u8], ikm: &[u8]) -> Result<HkdfPrk, Self::Error> {
+        Self::check_hash::<H>()?;
+        let zeros = [0u8; HKDF_MAX_HASH_SIZE];
+        let salt = if salt.is_empty() {
+            &zeros[..M::OUTPUT_SIZE]
+        } else {
+            salt
+        };
+        self.mac.set_key(salt).map_err(HkdfError::Mac)?;
+        self.absorb(ikm)?;
+        let mut prk = HkdfPrk {
+            bytes: [0; HKDF_MAX_HASH_SIZE],
+            len: M::OUTPUT_SIZE,
+        };
+        self.mac
+            .finalize(&mut prk.bytes[..M::OUTPUT_SIZE])
+            .map_err(HkdfError::Mac)?;
+        Ok(prk)
+    }
+}
+
+impl<H: HashMarker, M: Mac> KdfExpand<H> for Hkdf<M> {
+    fn expand(&mut self, prk: &HkdfPrk, info: &[u8], okm: &mut [u8]) -> Result<(), Self::Error> {
+        Self::check_hash::<H>()?;
+        if okm.len() > 255 * M::OUTPUT_SIZE {
+            return Err(HkdfError::OutputTooLong);
+        }
+        let mut t = [0u8; HKDF_MAX_HASH_SIZE];
+        let result = self.expand_blocks(prk, info, okm, &mut t[..M::OUTPUT_SIZE]);
+        t.zeroize();
+        if result.is_err() {
+            // Do not leave a partial output behind.
+            okm.zeroize();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hmac::Hmac;
+    use crate::testutil::{hex, Sha256, Sha256Hash, Sha512Hash};
+
+    type HkdfSha256 = Hkdf<Hmac<Sha256, 64>>;
+
+    fn hkdf() -> HkdfSha256 {
+        Hkdf::new(Hmac::new(Sha256::default()))
+    }
+
+    /// Runs extract and expand, checking the PRK and returning the OKM.
+    fn derive<const L: usize>(salt: &[u8], ikm: &[u8], info: &[u8], prk: &str) -> [u8; L] {
+        let mut kdf = hkdf();
+        let key = KdfExtract::<Sha256Hash>::extract(&mut kdf, salt, ikm).unwrap();
+        assert_eq!(key.as_ref(), hex::<32>(prk));
+        let mut okm = [0u8; L];
+        KdfExpand::<Sha256Hash>::expand(&mut kdf, &key, info, &mut okm).unwrap();
+        okm
+    }
+
+    #[test]
+    fn rfc5869_basic() {
+        // Test case 1.
+        let salt: [u8; 13] = core::array::from_fn(|i| i as u8);
+        let info: [u8; 10] = core::array::from_fn(|i| 0xf0 + i as u8);
+        let okm = derive::<42>(
+            &salt,
+            &[0x0b; 22],
+            &info,
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+        );
+        assert_eq!(
+            okm,
+            hex(concat!(
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf",
+                "34007208d5b887185865"
+            ))
+        );
+    }
+
+    #[test]
+    fn rfc5869_long_inputs() {
+        // Test case 2.
+        let ikm: [u8; 80] = core::array::from_fn(|i| i as u8);
+        let salt: [u8; 80] = core::array::from_fn(|i| 0x60 + i as u8);
+        let info: [u8; 80] = core::array::from_fn(|i| 0xb0 + i as u8);
+        let okm = derive::<82>(
+            &salt,
+            &ikm,
+            &info,
+            "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+        );
+        assert_eq!(
+            okm,
+            hex(concat!(
+                "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c",
+                "59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71",
+                "cc30c58179ec3e87c14c01d5c1f3434f1d87"
+            ))
+        );
+    }
+
+    #[test]
+    fn rfc5869_empty_salt_and_info() {
+        // Test case 3.
+        let okm = derive::<42>(
+            &[],
+            &[0x0b; 22],
+            &[],
+            "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+        );
+        assert_eq!(
+            okm,
+            hex(concat!(
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d",
+                "9d201395faa4b61a96c8"
+            ))
+        );
+    }
+
+    #[test]
+    fn output_longer_than_255_blocks_is_rejected() {
+        let mut kdf = hkdf();
+        let prk = HkdfPrk::from_bytes(&[0; 32]).unwrap();
+        let mut okm = [0u8; 255 * 32 + 1];
+        assert_eq!(
+            KdfExpand::<Sha256Hash>::expand(&mut kdf, &prk, &[], &mut okm),
+            Err(HkdfError::OutputTooLong)
+        );
+    }
+
+    #[test]
+    fn mismatched_hash_is_rejected() {
+        let mut kdf = hkdf();
+        assert!(matches!(
+            KdfExtract::<Sha512Hash>::extract(&mut kdf, &[], &[0; 16]),
+            Err(HkdfError::UnsupportedHash)
+        ));
+    }
+}
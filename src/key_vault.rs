@@ -0,0 +1,168 @@
+//! Key vaults: keys referenced by opaque handle that never leave the engine.
+//!
+//! Keys are imported, generated or derived into the vault and afterwards
+//! only used through a [`KeyHandle`]; handle-based variants of the ECDSA and
+//! MAC traits ([`EcdsaSignWithHandle`](crate::ecdsa::EcdsaSignWithHandle),
+//! [`MacWithHandle`](crate::mac::MacWithHandle)) accept the handle in place
+//! of raw key material.
+
+use crate::contract::ContractVersion;
+
+/// Error kind.
+///
+/// This represents a common set of key vault errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// No free key slot is left.
+    Full,
+
+    /// The handle does not refer to a key in the vault.
+    InvalidHandle,
+
+    /// The key's usage policy does not permit the operation.
+    UsageNotPermitted,
+
+    /// The key type is not supported, or the key material is malformed.
+    InvalidKey,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Opaque reference to a key held by a vault.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct KeyHandle(pub u32);
+
+/// Operations a key may be used for, fixed when the key enters the vault.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct KeyUsage(pub u32);
+
+impl KeyUsage {
+    pub const SIGN: Self = Self(1 << 0);
+    pub const MAC: Self = Self(1 << 1);
+    pub const DERIVE: Self = Self(1 << 2);
+    pub const ENCRYPT: Self = Self(1 << 3);
+    pub const DECRYPT: Self = Self(1 << 4);
+    pub const KEY_AGREEMENT: Self = Self(1 << 5);
+
+    /// Usage permitting both `self` and `other`.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns `true` if every usage in `other` is permitted.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Kind of key held in a vault slot.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum KeyType {
+    /// Elliptic-curve private key on the curve with this
+    /// [`EcdsaCurve::id`](crate::ecdsa::EcdsaCurve::id).
+    Ecc(u32),
+    /// Symmetric MAC key of the given size in bytes.
+    Mac(u16),
+    /// Symmetric cipher key of the given size in bytes.
+    Cipher(u16),
+    /// Key derivation secret of the given size in bytes.
+    Secret(u16),
+}
+
+/// Trait for managing keys in a vault.
+pub trait KeyVault: ErrorType {
+    /// Imports key material into the vault.
+    ///
+    /// # Parameters
+    /// - `key_type`: The kind of key.
+    /// - `usage`: Operations the key may be used for.
+    /// - `material`: The key in its standard encoding, e.g. unwrapped by
+    ///   [`KeyUnwrap`](crate::cipher::KeyUnwrap) during provisioning.
+    ///
+    /// # Returns
+    /// A result containing the handle of the imported key, or an error.
+    fn import(
+        &mut self,
+        key_type: KeyType,
+        usage: KeyUsage,
+        material: &[u8],
+    ) -> Result<KeyHandle, Self::Error>;
+
+    /// Generates a key inside the vault.
+    ///
+    /// # Returns
+    /// A result containing the handle of the generated key, or an error.
+    fn generate(&mut self, key_type: KeyType, usage: KeyUsage) -> Result<KeyHandle, Self::Error>;
+
+    /// Returns the type and usage of a key.
+    fn describe(&self, key: KeyHandle) -> Result<(KeyType, KeyUsage), Self::Error>;
+
+    /// Erases a key; the handle becomes invalid.
+    fn erase(&mut self, key: KeyHandle) -> Result<(), Self::Error>;
+}
+
+/// Trait for deriving keys inside a vault.
+pub trait KeyVaultDerive: KeyVault {
+    /// Derives a new key from a key with `KeyUsage::DERIVE`.
+    ///
+    /// The derived key stays in the vault; the KDF is defined by the
+    /// implementation, typically the NIST SP 800-108 counter mode KDF.
+    ///
+    /// # Parameters
+    /// - `parent`: The key derivation key.
+    /// - `label`: Purpose of the derived key.
+    /// - `context`: Information binding the derived key to its use.
+    /// - `key_type`: The kind of the derived key.
+    /// - `usage`: Operations the derived key may be used for.
+    ///
+    /// # Returns
+    /// A result containing the handle of the derived key, or an error.
+    fn derive(
+        &mut self,
+        parent: KeyHandle,
+        label: &[u8],
+        context: &[u8],
+        key_type: KeyType,
+        usage: KeyUsage,
+    ) -> Result<KeyHandle, Self::Error>;
+}
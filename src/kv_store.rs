@@ -0,0 +1,651 @@
+use crate::block_device::BlockDevice;
+use crate::contract::ContractVersion;
+use crate::journal::{fnv1a, word, FNV_OFFSET};
+use crate::range_check::check_aligned;
+use crate::units::ByteOffset;
+
+/// Error kind.
+///
+/// This represents a common set of key-value store errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The underlying storage reported an error.
+    Device,
+
+    /// There is no room left for another record, even after reclaiming space.
+    Full,
+
+    /// The value does not fit in a record.
+    ValueTooLarge,
+
+    /// The store geometry does not match the underlying storage.
+    InvalidGeometry,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by store implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Key of a stored value.
+pub type Key = u32;
+
+/// Persistent key-value store for small items such as counters and
+/// configuration.
+pub trait KeyValueStore: ErrorType {
+    /// Reads the value stored under `key`.
+    ///
+    /// # Parameters
+    ///
+    /// - `key`: Key to look up.
+    /// - `value`: Buffer receiving the value. Bytes which do not fit are
+    ///   dropped.
+    ///
+    /// # Returns
+    ///
+    /// The stored length of the value, or `None` if the key is not present.
+    fn get(&mut self, key: Key, value: &mut [u8]) -> Result<Option<usize>, Self::Error>;
+
+    /// Stores `value` under `key`, replacing any previous value.
+    ///
+    /// The previous value stays readable until the new one is committed, so
+    /// a power failure leaves either of them in place.
+    fn set(&mut self, key: Key, value: &[u8]) -> Result<(), Self::Error>;
+
+    /// Removes `key`. Removing an absent key is not an error.
+    fn remove(&mut self, key: Key) -> Result<(), Self::Error>;
+
+    /// Removes every key.
+    fn clear(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Error returned by [`LogStore`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogStoreError<E> {
+    /// Underlying block device error
+    Device(E),
+    /// No space left after reclaiming the oldest sector
+    Full,
+    /// Value larger than a record
+    ValueTooLarge,
+    /// Record size or region not aligned to the device geometry
+    InvalidGeometry,
+}
+
+impl<E: core::fmt::Debug> Error for LogStoreError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            LogStoreError::Device(_) => ErrorKind::Device,
+            LogStoreError::Full => ErrorKind::Full,
+            LogStoreError::ValueTooLarge => ErrorKind::ValueTooLarge,
+            LogStoreError::InvalidGeometry => ErrorKind::InvalidGeometry,
+        }
+    }
+}
+
+const MAGIC: u32 = 0x5453_564B; // "KVST"
+const HEADER_SIZE: usize = 20;
+const TOMBSTONE: u16 = 1;
+
+/// Header of a valid record.
+#[derive(Copy, Clone)]
+struct Record {
+    seq: u32,
+    key: Key,
+    len: usize,
+    tombstone: bool,
+}
+
+/// [`KeyValueStore`] kept as an append-only log in a region of a NOR-like
+/// [`BlockDevice`].
+///
+/// Each erase sector of the region holds `erase_size() / SLOT` records of
+/// `SLOT` bytes. Every update appends a record with the next sequence
+/// number and the newest record of a key wins. Sectors are filled in turn
+/// around the region, which spreads erases evenly.
+///
+/// One sector is always kept erased. When the head moves into it, the
+/// oldest sector, which is the next one around the region, is reclaimed:
+/// its live records are copied to the head and it is erased. A reset during
+/// reclaim is harmless, since the copies carry newer sequence numbers than
+/// the originals. The interrupted reclaim finishes on the next mount.
+///
+/// Lookups scan the whole region, so the store suits tens to hundreds of
+/// records. The live records of one sector must fit in one sector's worth
+/// of free space, otherwise updates fail with `Full`.
+///
+/// Record layout:
+///
+/// | Bytes  | Content                                             |
+/// |--------|-----------------------------------------------------|
+/// | 0..4   | magic, little endian                                |
+/// | 4..8   | sequence number, little endian                      |
+/// | 8..12  | key, little endian                                  |
+/// | 12..14 | value length, little endian                         |
+/// | 14..16 | flags, little endian; 1 marks a removal             |
+/// | 16..20 | FNV-1a of bytes 4..16 and the value                 |
+/// | 20..   | value                                               |
+pub struct LogStore<D, const SLOT: usize> {
+    device: D,
+    base: usize,
+    sector_size: usize,
+    sectors: u32,
+    head_sector: u32,
+    head_slot: u32,
+    next_seq: u32,
+}
+
+impl<D, const SLOT: usize> ErrorType for LogStore<D, SLOT>
+where
+    D: BlockDevice,
+{
+    type Error = LogStoreError<D::Error>;
+}
+
+impl<D, const SLOT: usize> LogStore<D, SLOT>
+where
+    D: BlockDevice,
+{
+    /// Mounts the store kept in `size` bytes at `base`.
+    ///
+    /// The region must span at least two erase sectors and must have been
+    /// erased (see [`KeyValueStore::clear`]) before first use. Existing
+    /// records are scanned to find the head of the log, and an interrupted
+    /// reclaim is completed.
+    ///
+    /// The device must support partial-page and bit-clearing programs.
+    pub fn new(device: D, base: ByteOffset, size: usize) -> Result<Self, LogStoreError<D::Error>> {
+        const {
+            assert!(
+                SLOT <= u16::MAX as usize + HEADER_SIZE,
+                "value length must fit the 16-bit length field"
+            )
+        };
+        let base = base.get();
+        let unit = device.program_size();
+        let sector_size = device.erase_size();
+        let caps = device.program_capabilities();
+        if !caps.partial_page
+            || !caps.bit_clearing
+            || unit == 0
+            || !SLOT.is_multiple_of(unit)
+            || SLOT <= HEADER_SIZE
+            || SLOT > sector_size
+            || !sector_size.is_multiple_of(SLOT)
+            || size / sector_size.max(1) < 2
+            || check_aligned(base, size, sector_size, device.capacity()).is_err()
+        {
+            return Err(LogStoreError::InvalidGeometry);
+        }
+
+        let mut store = Self {
+            device,
+            base,
+            sector_size,
+            sectors: (size / sector_size) as u32,
+            head_sector: 0,
+            head_slot: 0,
+            next_seq: 0,
+        };
+
+        let mut newest: Option<(u32, u32)> = None;
+        for sector in 0..store.sectors {
+            for slot in 0..store.slots_per_sector() {
+                if let Some(record) = store.read_record(sector, slot)? {
+                    if newest.is_none_or(|(seq, _)| record.seq > seq) {
+                        newest = Some((record.seq, sector));
+                    }
+                }
+            }
+        }
+        if let Some((seq, sector)) = newest {
+            store.head_sector = sector;
+            store.next_seq = seq.wrapping_add(1);
+        }
+        // Torn slots are skipped as well, since NOR cells can't be reprogrammed.
+        while store.head_slot < store.slots_per_sector()
+            && store.slot_used(store.head_sector, store.head_slot)?
+        {
+            store.head_slot += 1;
+        }
+
+        store.reclaim()?;
+        Ok(store)
+    }
+
+    /// Releases the underlying device.
+    pub fn release(self) -> D {
+        self.device
+    }
+
+    /// Largest value a single record can hold.
+    pub fn max_value(&self) -> usize {
+        SLOT - HEADER_SIZE
+    }
+
+    fn slots_per_sector(&self) -> u32 {
+        (self.sector_size / SLOT) as u32
+    }
+
+    fn next_sector(&self, sector: u32) -> u32 {
+        (sector + 1) % self.sectors
+    }
+
+    fn slot_addr(&self, sector: u32, slot: u32) -> usize {
+        self.base + sector as usize * self.sector_size + slot as usize * SLOT
+    }
+
+    fn slot_used(&mut self, sector: u32, slot: u32) -> Result<bool, LogStoreError<D::Error>> {
+        let mut header = [0u8; HEADER_SIZE];
+        self.device
+            .read(ByteOffset(self.slot_addr(sector, slot)), &mut header)
+            .map_err(LogStoreError::Device)?;
+        Ok(header.iter().any(|&b| b != 0xFF))
+    }
+
+    fn sector_erased(&mut self, sector: u32) -> Result<bool, LogStoreError<D::Error>> {
+        for slot in 0..self.slots_per_sector() {
+            if self.slot_used(sector, slot)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads the record in a slot, returning `None` for erased or torn slots.
+    fn read_record(
+        &mut self,
+        sector: u32,
+        slot: u32,
+    ) -> Result<Option<Record>, LogStoreError<D::Error>> {
+        let addr = self.slot_addr(sector, slot);
+        let mut header = [0u8; HEADER_SIZE];
+        self.device
+            .read(ByteOffset(addr), &mut header)
+            .map_err(LogStoreError::Device)?;
+
+        if word(&header, 0) != MAGIC {
+            return Ok(None);
+        }
+        let len = u16::from_le_bytes([header[12], header[13]]) as usize;
+        if len > self.max_value() {
+            return Ok(None);
+        }
+
+        let mut hash = fnv1a(FNV_OFFSET, &header[4..16]);
+        let mut chunk = [0u8; 32];
+        let mut done = 0;
+        while done < len {
+            let n = (len - done).min(chunk.len());
+            self.device
+                .read(ByteOffset(addr + HEADER_SIZE + done), &mut chunk[..n])
+                .map_err(LogStoreError::Device)?;
+            hash = fnv1a(hash, &chunk[..n]);
+            done += n;
+        }
+
+        // A torn write leaves a header whose checksum does not match.
+        Ok((hash == word(&header, 16)).then(|| Record {
+            seq: word(&header, 4),
+            key: word(&header, 8),
+            len,
+            tombstone: u16::from_le_bytes([header[14], header[15]]) == TOMBSTONE,
+        }))
+    }
+
+    /// Finds the newest record of `key`, returning it with its slot address.
+    fn find(&mut self, key: Key) -> Result<Option<(Record, usize)>, LogStoreError<D::Error>> {
+        let mut newest: Option<(Record, usize)> = None;
+        for sector in 0..self.sectors {
+            for slot in 0..self.slots_per_sector() {
+                let Some(record) = self.read_record(sector, slot)? else {
+                    continue;
+                };
+                if record.key == key && newest.is_none_or(|(r, _)| record.seq > r.seq) {
+                    newest = Some((record, self.slot_addr(sector, slot)));
+                }
+            }
+        }
+        Ok(newest)
+    }
+
+    fn append(
+        &mut self,
+        key: Key,
+        value: &[u8],
+        tombstone: bool,
+    ) -> Result<(), LogStoreError<D::Error>> {
+        if value.len() > self.max_value() {
+            return Err(LogStoreError::ValueTooLarge);
+        }
+        if self.head_slot >= self.slots_per_sector() {
+            self.advance()?;
+            // The reclaimed records took the whole new head sector.
+            if self.head_slot >= self.slots_per_sector() {
+                return Err(LogStoreError::Full);
+            }
+        }
+
+        let mut slot = [0xFFu8; SLOT];
+        slot[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        slot[4..8].copy_from_slice(&self.next_seq.to_le_bytes());
+        slot[8..12].copy_from_slice(&key.to_le_bytes());
+        slot[12..14].copy_from_slice(&(value.len() as u16).to_le_bytes());
+        slot[14..16].copy_from_slice(&(tombstone as u16).to_le_bytes());
+        slot[HEADER_SIZE..HEADER_SIZE + value.len()].copy_from_slice(value);
+        let hash = fnv1a(fnv1a(FNV_OFFSET, &slot[4..16]), value);
+        slot[16..20].copy_from_slice(&hash.to_le_bytes());
+
+        self.device
+            .program(
+                ByteOffset(self.slot_addr(self.head_sector, self.head_slot)),
+                &slot,
+            )
+            .map_err(LogStoreError::Device)?;
+        self.head_slot += 1;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Moves the head into the spare sector and reclaims the oldest one.
+    fn advance(&mut self) -> Result<(), LogStoreError<D::Error>> {
+        let next = self.next_sector(self.head_sector);
+        // Only happens if a reclaim filled the whole head sector.
+        if !self.sector_erased(next)? {
+            return Err(LogStoreError::Full);
+        }
+        self.head_sector = next;
+        self.head_slot = 0;
+        self.reclaim()
+    }
+
+    /// Copies the live records of the sector after the head to the head and
+    /// erases it, unless it is already erased.
+    fn reclaim(&mut self) -> Result<(), LogStoreError<D::Error>> {
+        let victim = self.next_sector(self.head_sector);
+        if self.sector_erased(victim)? {
+            return Ok(());
+        }
+
+        let mut value = [0u8; SLOT];
+        for slot in 0..self.slots_per_sector() {
+            let Some(record) = self.read_record(victim, slot)? else {
+                continue;
+            };
+            // Removals can be dropped: older records of the key can only
+            // live in this sector, which is about to be erased.
+            if record.tombstone {
+                continue;
+            }
+            let is_newest = self
+                .find(record.key)?
+                .is_some_and(|(newest, _)| newest.seq == record.seq);
+            if !is_newest {
+                continue;
+            }
+
+            let value = &mut value[..record.len];
+            self.device
+                .read(
+                    ByteOffset(self.slot_addr(victim, slot) + HEADER_SIZE),
+                    value,
+                )
+                .map_err(LogStoreError::Device)?;
+            self.append(record.key, value, false)?;
+        }
+
+        self.device
+            .erase(ByteOffset(self.slot_addr(victim, 0)), self.sector_size)
+            .map_err(LogStoreError::Device)
+    }
+}
+
+impl<D, const SLOT: usize> KeyValueStore for LogStore<D, SLOT>
+where
+    D: BlockDevice,
+{
+    fn get(&mut self, key: Key, value: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+        let Some((record, addr)) = self.find(key)? else {
+            return Ok(None);
+        };
+        if record.tombstone {
+            return Ok(None);
+        }
+
+        let n = record.len.min(value.len());
+        self.device
+            .read(ByteOffset(addr + HEADER_SIZE), &mut value[..n])
+            .map_err(LogStoreError::Device)?;
+        Ok(Some(record.len))
+    }
+
+    fn set(&mut self, key: Key, value: &[u8]) -> Result<(), Self::Error> {
+        self.append(key, value, false)
+    }
+
+    fn remove(&mut self, key: Key) -> Result<(), Self::Error> {
+        match self.find(key)? {
+            Some((record, _)) if !record.tombstone => self.append(key, &[], true),
+            _ => Ok(()),
+        }
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.device
+            .erase(
+                ByteOffset(self.base),
+                self.sectors as usize * self.sector_size,
+            )
+            .map_err(LogStoreError::Device)?;
+        self.head_sector = 0;
+        self.head_slot = 0;
+        self.next_seq = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_device::ProgramCapabilities;
+    use crate::testutil::NorMock;
+
+    /// Three 256-byte sectors of four 64-byte slots each.
+    type TestStore = LogStore<NorMock<1024>, 64>;
+
+    fn open(device: NorMock<1024>) -> TestStore {
+        LogStore::new(device, ByteOffset(0), 768).unwrap()
+    }
+
+    fn get(store: &mut TestStore, key: Key) -> Option<u8> {
+        let mut value = [0u8; 1];
+        store.get(key, &mut value).unwrap().map(|len| {
+            assert_eq!(len, 1);
+            value[0]
+        })
+    }
+
+    fn erased_sectors(device: &NorMock<1024>) -> usize {
+        device.data[..768]
+            .chunks(256)
+            .filter(|sector| sector.iter().all(|&b| b == 0xFF))
+            .count()
+    }
+
+    #[test]
+    fn set_get_and_remove() {
+        let mut store = open(NorMock::new(256));
+        assert_eq!(get(&mut store, 1), None);
+
+        store.set(1, &[10]).unwrap();
+        store.set(2, &[20]).unwrap();
+        store.set(1, &[11]).unwrap();
+        assert_eq!(get(&mut store, 1), Some(11));
+        assert_eq!(get(&mut store, 2), Some(20));
+
+        store.remove(2).unwrap();
+        store.remove(3).unwrap();
+        assert_eq!(get(&mut store, 2), None);
+
+        let mut value = [0u8; 8];
+        store.set(4, &[1, 2, 3]).unwrap();
+        assert_eq!(store.get(4, &mut value[..2]), Ok(Some(3)));
+        assert_eq!(value[..2], [1, 2]);
+    }
+
+    #[test]
+    fn reclaim_rotates_spare_sector_and_keeps_live_values() {
+        let mut store = open(NorMock::new(256));
+        store.set(1, &[0xAA]).unwrap();
+        // 30 records go around the 12 slots more than twice.
+        for i in 0..30 {
+            store.set(2, &[i]).unwrap();
+            assert_eq!(get(&mut store, 1), Some(0xAA));
+            assert_eq!(get(&mut store, 2), Some(i));
+        }
+
+        let device = store.release();
+        assert!(erased_sectors(&device) >= 1);
+        let mut store = open(device);
+        assert_eq!(get(&mut store, 1), Some(0xAA));
+        assert_eq!(get(&mut store, 2), Some(29));
+        store.set(3, &[3]).unwrap();
+        assert_eq!(get(&mut store, 3), Some(3));
+    }
+
+    #[test]
+    fn reclaim_drops_removed_keys() {
+        let mut store = open(NorMock::new(256));
+        store.set(1, &[1]).unwrap();
+        store.remove(1).unwrap();
+        for i in 0..12 {
+            store.set(2, &[i]).unwrap();
+        }
+        assert_eq!(get(&mut store, 1), None);
+        assert_eq!(get(&mut store, 2), Some(11));
+    }
+
+    #[test]
+    fn full_when_live_records_fill_every_sector() {
+        let mut store = open(NorMock::new(256));
+        // Eight live keys fill both usable sectors; the ninth has no room.
+        for key in 0..8 {
+            store.set(key, &[key as u8]).unwrap();
+        }
+        assert_eq!(store.set(8, &[8]), Err(LogStoreError::Full));
+        assert_eq!(store.set(8, &[8]), Err(LogStoreError::Full));
+        for key in 0..8 {
+            assert_eq!(get(&mut store, key), Some(key as u8));
+        }
+        assert!(store.release().data[768..].iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn torn_update_keeps_previous_value() {
+        let mut store = open(NorMock::new(256));
+        store.set(1, &[1]).unwrap();
+
+        let mut device = store.release();
+        device.cut_power_after(10);
+        let mut store = open(device);
+        assert_eq!(
+            store.set(1, &[2]),
+            Err(LogStoreError::Device(
+                crate::testutil::NorMockError::PowerLoss
+            ))
+        );
+
+        let mut device = store.release();
+        device.power_on();
+        let mut store = open(device);
+        assert_eq!(get(&mut store, 1), Some(1));
+        store.set(1, &[3]).unwrap();
+        assert_eq!(get(&mut store, 1), Some(3));
+    }
+
+    #[test]
+    fn clear_removes_every_key() {
+        let mut store = open(NorMock::new(256));
+        store.set(1, &[1]).unwrap();
+        store.set(2, &[2]).unwrap();
+        store.clear().unwrap();
+        assert_eq!(get(&mut store, 1), None);
+        assert_eq!(get(&mut store, 2), None);
+        assert_eq!(erased_sectors(&store.release()), 3);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let mut store = open(NorMock::new(256));
+        assert_eq!(store.max_value(), 44);
+        assert_eq!(store.set(1, &[0; 45]), Err(LogStoreError::ValueTooLarge));
+
+        // A single sector leaves no spare.
+        assert!(matches!(
+            TestStore::new(NorMock::new(256), ByteOffset(0), 256),
+            Err(LogStoreError::InvalidGeometry)
+        ));
+        // Region not aligned to sectors.
+        assert!(matches!(
+            TestStore::new(NorMock::new(256), ByteOffset(128), 512),
+            Err(LogStoreError::InvalidGeometry)
+        ));
+        // Slots don't divide the sector.
+        assert!(matches!(
+            LogStore::<_, 48>::new(NorMock::<1024>::new(256), ByteOffset(0), 512),
+            Err(LogStoreError::InvalidGeometry)
+        ));
+    }
+
+    #[test]
+    fn devices_without_nor_semantics_are_rejected() {
+        for caps in [
+            ProgramCapabilities::default(),
+            ProgramCapabilities {
+                partial_page: true,
+                ..Default::default()
+            },
+            ProgramCapabilities {
+                bit_clearing: true,
+                ..Default::default()
+            },
+        ] {
+            let mut device = NorMock::new(256);
+            device.caps = caps;
+            assert!(matches!(
+                TestStore::new(device, ByteOffset(0), 768),
+                Err(LogStoreError::InvalidGeometry)
+            ));
+        }
+    }
+}
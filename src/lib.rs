@@ -1,10 +1,47 @@
 #![no_std]
 #![deny(unsafe_code)]
 
+pub mod algorithm;
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod rsa;
 pub mod ecdsa;
 pub mod mac;
 pub mod digest;
 
 
-pub mod block_device;
\ No newline at end of file
+pub mod block_device;
+pub mod budget;
+pub mod build_info;
+pub mod capability;
+pub mod chunked;
+pub mod cipher;
+pub mod common;
+pub mod contract;
+pub mod ecdh;
+pub mod eddsa;
+#[cfg(feature = "error-agg")]
+pub mod error;
+pub mod fallback;
+pub mod fault;
+pub mod hmac;
+pub mod hybrid;
+pub mod journal;
+pub mod kdf;
+pub mod key_vault;
+pub mod kv_store;
+pub mod measurement;
+pub mod osal;
+pub mod otp;
+pub mod otp_image;
+pub mod pq;
+pub mod range_check;
+pub mod rng;
+#[cfg(test)]
+mod testutil;
+pub mod units;
+pub mod x25519;
+pub mod xip;
+
+#[cfg(feature = "error-agg")]
+pub use error::AnyPeripheralError;
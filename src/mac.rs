@@ -1,9 +1,17 @@
+use core::marker::PhantomData;
+
+use crate::algorithm::AlgorithmId;
+use crate::common::ConstantTimeEq;
+use crate::contract::ContractVersion;
+use crate::key_vault::KeyHandle;
+
 /// Error kind.
 ///
 /// This represents a common set of digest operation errors. Implementations are
 /// free to define more specific or additional error types. However, by providing
 /// a mapping to these common errors, generic code can still react to them.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum ErrorKind {
     /// The input data length is not valid for the hash function.
@@ -38,6 +46,32 @@ pub enum ErrorKind {
 
     /// The hash computation context has not been initialized.
     NotInitialized,
+
+    /// The computed MAC tag does not match the expected one.
+    VerificationFailed,
+
+    /// The key handle is invalid or its usage does not permit the operation.
+    InvalidKeyHandle,
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ErrorKind::InvalidInputLength => "invalid input length",
+            ErrorKind::UnsupportedAlgorithm => "unsupported algorithm",
+            ErrorKind::MemoryAllocationFailure => "memory allocation failed",
+            ErrorKind::InitializationError => "initialization failed",
+            ErrorKind::UpdateError => "update failed",
+            ErrorKind::FinalizationError => "finalization failed",
+            ErrorKind::HardwareAcceleratorBusy => "engine busy",
+            ErrorKind::HardwareFailure => "hardware failure",
+            ErrorKind::InvalidOutputSize => "invalid output size",
+            ErrorKind::PermissionDenied => "permission denied",
+            ErrorKind::NotInitialized => "not initialized",
+            ErrorKind::VerificationFailed => "tag mismatch",
+            ErrorKind::InvalidKeyHandle => "invalid key handle",
+        })
+    }
 }
 
 pub trait Error: core::fmt::Debug {
@@ -60,14 +94,30 @@ impl Error for core::convert::Infallible {
     }
 }
 
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
 pub trait ErrorType {
     /// Error type.
     type Error: Error;
 }
 
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
 /// Message Authentication algorithm
 pub trait Mac: ErrorType {
     type InitParams;
+
+    /// Size of the MAC tag in bytes.
+    const OUTPUT_SIZE: usize;
+
     /// Init instance of the crypto function with the given context.
     ///
     /// # Parameters
@@ -112,13 +162,27 @@ pub trait Mac: ErrorType {
     ///
     /// # Parameters
     ///
-    /// - `out`: A mutable slice to store the hash output. The length of the slice must be at least `MAX_OUTPUT_SIZE`.
+    /// - `out`: A mutable slice to store the hash output. The length of the slice must be at least `OUTPUT_SIZE`.
     ///
     /// # Returns
     ///
     /// A `Result` indicating success or failure. On success, returns `Ok(())`. On failure, returns a `CryptoError`.    
     fn finalize(&mut self, out: &mut [u8]) -> Result<(), Self::Error>;
 
+    /// Finalize the computation and return the tag as an array.
+    ///
+    /// `N` must equal `OUTPUT_SIZE`; a mismatch fails to compile.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the MAC tag.
+    fn finalize_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+        const { assert!(N == Self::OUTPUT_SIZE, "array length must equal OUTPUT_SIZE") };
+        let mut out = [0u8; N];
+        self.finalize(&mut out)?;
+        Ok(out)
+    }
+
     /// Verifies if the given MAC tag matches the expected result.
     ///
     /// # Parameters
@@ -130,3 +194,319 @@ pub trait Mac: ErrorType {
     /// A `Result` indicating success or failure. On success, returns `Ok(())`. On failure, returns an error of type `Self::Error`.    
     fn verify(&mut self, tag: &[u8]) -> Result<(), Self::Error>;
 }
+
+/// MAC algorithm marker.
+///
+/// Describes an algorithm independently of the engine computing it, so
+/// hash-based (HMAC, KMAC) and cipher-based (CMAC) MACs share one trait
+/// family.
+pub trait MacAlgorithm {
+    /// Algorithm identifier.
+    const ID: AlgorithmId;
+    /// Shortest accepted key, in bytes.
+    const MIN_KEY_SIZE: usize;
+    /// Longest accepted key, in bytes.
+    const MAX_KEY_SIZE: usize;
+    /// Size of the tag in bytes.
+    const OUTPUT_SIZE: usize;
+}
+
+/// HMAC-SHA-256 (RFC 2104).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct HmacSha256;
+
+impl MacAlgorithm for HmacSha256 {
+    const ID: AlgorithmId = AlgorithmId::HMAC_SHA2_256;
+    const MIN_KEY_SIZE: usize = 1;
+    const MAX_KEY_SIZE: usize = usize::MAX;
+    const OUTPUT_SIZE: usize = 32;
+}
+
+/// HMAC-SHA-384 (RFC 2104).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct HmacSha384;
+
+impl MacAlgorithm for HmacSha384 {
+    const ID: AlgorithmId = AlgorithmId::HMAC_SHA2_384;
+    const MIN_KEY_SIZE: usize = 1;
+    const MAX_KEY_SIZE: usize = usize::MAX;
+    const OUTPUT_SIZE: usize = 48;
+}
+
+/// HMAC-SHA-512 (RFC 2104).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct HmacSha512;
+
+impl MacAlgorithm for HmacSha512 {
+    const ID: AlgorithmId = AlgorithmId::HMAC_SHA2_512;
+    const MIN_KEY_SIZE: usize = 1;
+    const MAX_KEY_SIZE: usize = usize::MAX;
+    const OUTPUT_SIZE: usize = 64;
+}
+
+/// AES-CMAC (NIST SP 800-38B) with a 128-bit key.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct AesCmac128;
+
+impl MacAlgorithm for AesCmac128 {
+    const ID: AlgorithmId = AlgorithmId::AES_128_CMAC;
+    const MIN_KEY_SIZE: usize = 16;
+    const MAX_KEY_SIZE: usize = 16;
+    const OUTPUT_SIZE: usize = 16;
+}
+
+/// AES-CMAC (NIST SP 800-38B) with a 192-bit key.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct AesCmac192;
+
+impl MacAlgorithm for AesCmac192 {
+    const ID: AlgorithmId = AlgorithmId::AES_192_CMAC;
+    const MIN_KEY_SIZE: usize = 24;
+    const MAX_KEY_SIZE: usize = 24;
+    const OUTPUT_SIZE: usize = 16;
+}
+
+/// AES-CMAC (NIST SP 800-38B) with a 256-bit key.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct AesCmac256;
+
+impl MacAlgorithm for AesCmac256 {
+    const ID: AlgorithmId = AlgorithmId::AES_256_CMAC;
+    const MIN_KEY_SIZE: usize = 32;
+    const MAX_KEY_SIZE: usize = 32;
+    const OUTPUT_SIZE: usize = 16;
+}
+
+/// KMAC128 (NIST SP 800-185) with a 256-bit tag.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Kmac128;
+
+impl MacAlgorithm for Kmac128 {
+    const ID: AlgorithmId = AlgorithmId::KMAC128;
+    const MIN_KEY_SIZE: usize = 16;
+    const MAX_KEY_SIZE: usize = usize::MAX;
+    const OUTPUT_SIZE: usize = 32;
+}
+
+/// KMAC256 (NIST SP 800-185) with a 512-bit tag.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Kmac256;
+
+impl MacAlgorithm for Kmac256 {
+    const ID: AlgorithmId = AlgorithmId::KMAC256;
+    const MIN_KEY_SIZE: usize = 32;
+    const MAX_KEY_SIZE: usize = usize::MAX;
+    const OUTPUT_SIZE: usize = 64;
+}
+
+/// MAC engine supporting the algorithm `A`.
+///
+/// An engine implements this once per algorithm it supports, e.g. an AES
+/// block for [`AesCmac128`] and [`AesCmac256`], and a SHA-3 block for
+/// [`Kmac128`].
+pub trait MacInit<A: MacAlgorithm>: ErrorType {
+    /// Operation computing one MAC.
+    type Op<'a>: Mac<Error = Self::Error>
+    where
+        Self: 'a;
+
+    /// Starts a MAC operation.
+    ///
+    /// # Parameters
+    ///
+    /// - `algorithm`: The algorithm marker.
+    /// - `key`: The key, between `A::MIN_KEY_SIZE` and `A::MAX_KEY_SIZE` bytes long.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the keyed operation. Fails with `ErrorKind::InvalidInputLength` if the key size is out of range.
+    fn init<'a>(&'a mut self, algorithm: A, key: &[u8]) -> Result<Self::Op<'a>, Self::Error>;
+}
+
+/// Object-safe MAC operation with the algorithm chosen at runtime.
+///
+/// Errors are reported as [`ErrorKind`] so operations of different engines
+/// can be handled through one `dyn` type.
+pub trait DynamicMacOp {
+    /// Algorithm computed by this operation.
+    fn algorithm(&self) -> AlgorithmId;
+
+    /// Size of the tag in bytes.
+    fn output_size(&self) -> usize;
+
+    /// Update state using provided input data.
+    fn update(&mut self, input: &mut [u8]) -> Result<(), ErrorKind>;
+
+    /// Finalize the computation and write the tag to `out[..output_size()]`.
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), ErrorKind>;
+
+    /// Finalize the computation and compare the tag with `expected` in constant time.
+    fn verify(&mut self, expected: &[u8]) -> Result<(), ErrorKind>;
+}
+
+/// Creates MAC operations by algorithm identifier.
+///
+/// For protocols that negotiate the MAC algorithm at runtime, such as SPDM
+/// and TLS. The registry owns the operation storage, so at most one
+/// operation created by it is live at a time.
+pub trait MacRegistry {
+    /// Returns `true` if operations for `algorithm` can be created.
+    fn supports(&self, algorithm: AlgorithmId) -> bool;
+
+    /// Starts a keyed MAC operation.
+    ///
+    /// # Parameters
+    ///
+    /// - `algorithm`: The negotiated algorithm.
+    /// - `key`: The key.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the operation. Fails with `ErrorKind::UnsupportedAlgorithm` if `algorithm` is not supported.
+    fn create(
+        &mut self,
+        algorithm: AlgorithmId,
+        key: &[u8],
+    ) -> Result<&mut dyn DynamicMacOp, ErrorKind>;
+}
+
+/// Largest tag size supported by [`DynMac`].
+pub const DYN_MAX_OUTPUT_SIZE: usize = 64;
+
+/// [`DynamicMacOp`] over a [`Mac`] computing the algorithm `A`.
+pub struct DynMac<M, A> {
+    mac: M,
+    _algorithm: PhantomData<A>,
+}
+
+impl<M: Mac, A: MacAlgorithm> DynMac<M, A> {
+    pub fn new(mac: M) -> Self {
+        const {
+            assert!(
+                M::OUTPUT_SIZE == A::OUTPUT_SIZE && M::OUTPUT_SIZE <= DYN_MAX_OUTPUT_SIZE,
+                "MAC output size must match the algorithm and fit DYN_MAX_OUTPUT_SIZE"
+            )
+        };
+        Self {
+            mac,
+            _algorithm: PhantomData,
+        }
+    }
+
+    /// Releases the MAC.
+    pub fn release(self) -> M {
+        self.mac
+    }
+}
+
+impl<M: Mac, A: MacAlgorithm> DynamicMacOp for DynMac<M, A> {
+    fn algorithm(&self) -> AlgorithmId {
+        A::ID
+    }
+
+    fn output_size(&self) -> usize {
+        A::OUTPUT_SIZE
+    }
+
+    fn update(&mut self, input: &mut [u8]) -> Result<(), ErrorKind> {
+        self.mac.update(input).map_err(|e| e.kind())
+    }
+
+    fn finalize(&mut self, out: &mut [u8]) -> Result<(), ErrorKind> {
+        self.mac.finalize(out).map_err(|e| e.kind())
+    }
+
+    fn verify(&mut self, expected: &[u8]) -> Result<(), ErrorKind> {
+        let mut tag = [0u8; DYN_MAX_OUTPUT_SIZE];
+        let tag = &mut tag[..A::OUTPUT_SIZE];
+        self.mac.finalize(tag).map_err(|e| e.kind())?;
+        if constant_time_eq(tag, expected) {
+            Ok(())
+        } else {
+            Err(ErrorKind::VerificationFailed)
+        }
+    }
+}
+
+/// MAC keyed with a key held in a key vault.
+///
+/// The key is referenced by [`KeyHandle`] and never leaves the engine.
+pub trait MacWithHandle: Mac {
+    /// Sets the key to a vault-held key, in place of [`Mac::set_key`].
+    ///
+    /// # Parameters
+    ///
+    /// - `key`: Handle of a key with `KeyUsage::MAC`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or an error of kind `InvalidKeyHandle`.
+    fn set_key_handle(&mut self, key: KeyHandle) -> Result<(), Self::Error>;
+}
+
+/// Save and restore of the MAC context.
+///
+/// Lets a MAC engine shared between sessions (e.g. SPDM sessions each keeping
+/// a transcript MAC) interleave computations without recomputing from the
+/// start. It is only meant to be restored on the same engine.
+///
+/// A saved context is key material: it may hold the key itself, as
+/// [`Hmac`](crate::hmac::Hmac) contexts do. Keep it in memory no less
+/// protected than the key, and clear the buffer with
+/// [`Zeroize`](crate::common::Zeroize) once it has been restored or is no
+/// longer needed.
+pub trait ResumableMac: Mac {
+    /// Saves the current MAC context, including the keyed state.
+    ///
+    /// The written bytes are key material; see the trait documentation.
+    ///
+    /// # Parameters
+    ///
+    /// - `buf`: A mutable slice receiving the saved context.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written. Fails with `ErrorKind::InvalidOutputSize` if `buf` is too small.
+    fn save_context(&self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Restores a previously saved MAC context.
+    ///
+    /// # Parameters
+    ///
+    /// - `buf`: Context written by `save_context`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. Fails with `ErrorKind::InvalidInputLength` if `buf` is not a valid context.
+    fn restore_context(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// MAC verification that never hands the computed tag to the caller.
+///
+/// Finalizing and comparing tags in caller code invites early-exit
+/// comparisons that leak, through timing, how many leading bytes of a forged
+/// tag were right. Implementations compare internally in constant time,
+/// e.g. with [`constant_time_eq`] or in the MAC engine itself.
+pub trait MacVerify: Mac {
+    /// Finalizes the computation and compares the result with `expected`.
+    ///
+    /// Consumes the instance, so the computed tag cannot be read back afterwards.
+    ///
+    /// # Parameters
+    ///
+    /// - `expected`: The MAC tag received with the message.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the tags match, otherwise an error of kind `VerificationFailed`.
+    fn finalize_and_verify(self, expected: &[u8]) -> Result<(), Self::Error>
+    where
+        Self: Sized;
+}
+
+/// Compares two byte strings in time independent of their contents.
+///
+/// Only the lengths, which are public for MAC tags, affect the timing.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b)
+}
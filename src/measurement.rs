@@ -0,0 +1,172 @@
+//! Measurement evidence in SPDM (DMTF DSP0274) block format.
+
+use crate::contract::ContractVersion;
+
+/// Error kind.
+///
+/// This represents a common set of measurement errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// No measurement exists at the requested index.
+    InvalidIndex,
+
+    /// The output buffer is too small for the measurement value.
+    BufferTooSmall,
+
+    /// The measurement source could not be read.
+    SourceUnavailable,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// DMTF measurement value type (bits 0..=6 of `DMTFSpecMeasurementValueType`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MeasurementType {
+    ImmutableRom,
+    MutableFirmware,
+    HardwareConfig,
+    FirmwareConfig,
+    MeasurementManifest,
+    DeviceMode,
+    MutableFirmwareVersion,
+    MutableFirmwareSecurityVersion,
+    Other(u8),
+}
+
+impl MeasurementType {
+    fn code(self) -> u8 {
+        match self {
+            MeasurementType::ImmutableRom => 0x00,
+            MeasurementType::MutableFirmware => 0x01,
+            MeasurementType::HardwareConfig => 0x02,
+            MeasurementType::FirmwareConfig => 0x03,
+            MeasurementType::MeasurementManifest => 0x04,
+            MeasurementType::DeviceMode => 0x05,
+            MeasurementType::MutableFirmwareVersion => 0x06,
+            MeasurementType::MutableFirmwareSecurityVersion => 0x07,
+            MeasurementType::Other(code) => code & 0x7F,
+        }
+    }
+}
+
+/// How the measurement value is represented.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Representation {
+    /// The value is a digest of the measured object.
+    Digest,
+    /// The value is the measured object itself.
+    RawBitStream,
+}
+
+/// Description of a measurement whose value was written by a provider.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MeasurementInfo {
+    /// Measurement index, 1 through 0xFE.
+    pub index: u8,
+    /// Kind of object measured.
+    pub measurement_type: MeasurementType,
+    /// Whether the value is a digest or raw data.
+    pub representation: Representation,
+    /// Length of the value in bytes.
+    pub len: usize,
+}
+
+impl MeasurementInfo {
+    /// Size of the block headers preceding the value.
+    pub const HEADER_SIZE: usize = 7;
+
+    /// Size of the encoded measurement block.
+    pub fn block_size(&self) -> usize {
+        Self::HEADER_SIZE + self.len
+    }
+
+    /// Writes the SPDM measurement block headers into `out`.
+    ///
+    /// The value itself is expected at `out[HEADER_SIZE..][..len]`, which
+    /// lets providers write values in place before encoding the headers.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes of the complete block, or `None` if `out` is too
+    /// small or the value does not fit the 16-bit size fields.
+    pub fn encode_header(&self, out: &mut [u8]) -> Option<usize> {
+        let value_size = u16::try_from(self.len).ok()?;
+        let measurement_size = value_size.checked_add(3)?;
+        if out.len() < self.block_size() {
+            return None;
+        }
+
+        let raw = match self.representation {
+            Representation::Digest => 0x00,
+            Representation::RawBitStream => 0x80,
+        };
+        out[0] = self.index;
+        // MeasurementSpecification bit 0: DMTF
+        out[1] = 0x01;
+        out[2..4].copy_from_slice(&measurement_size.to_le_bytes());
+        out[4] = raw | self.measurement_type.code();
+        out[5..7].copy_from_slice(&value_size.to_le_bytes());
+        Some(self.block_size())
+    }
+}
+
+/// Source of measurement evidence.
+///
+/// Implemented by subsystems holding evidence (measurement registers, OTP
+/// region digests, firmware image digests) so an SPDM responder can enumerate
+/// measurement blocks without knowing where each one comes from.
+pub trait MeasurementProvider: ErrorType {
+    /// Returns the number of measurements available.
+    fn measurement_count(&self) -> u8;
+
+    /// Returns the index of the `n`th measurement, `n < measurement_count()`.
+    fn measurement_index(&self, n: u8) -> Option<u8>;
+
+    /// Produces the measurement at `index`.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: Measurement index as reported by `measurement_index`.
+    /// - `value`: Buffer receiving the measurement value.
+    ///
+    /// # Returns
+    ///
+    /// A description of the value written into `value`.
+    fn measurement(&mut self, index: u8, value: &mut [u8]) -> Result<MeasurementInfo, Self::Error>;
+}
@@ -0,0 +1,88 @@
+//! Operating system abstraction for driver services.
+//!
+//! Lets drivers and their services use notifications, locks and other OS
+//! facilities portably across POSIX hosts, RTOS kernels and bare-metal
+//! executors.
+
+use crate::contract::ContractVersion;
+
+pub mod notify;
+pub mod shm;
+pub mod sync;
+pub mod task;
+
+/// Error kind.
+///
+/// This represents a common set of OS service errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The timeout expired before the operation could complete.
+    TimedOut,
+
+    /// The operation would block and `Timeout::NoWait` was given.
+    WouldBlock,
+
+    /// An argument is not valid for the object, e.g. bits outside its mask.
+    InvalidArgument,
+
+    /// The OS ran out of a resource, e.g. objects or memory.
+    ResourceExhausted,
+
+    /// The caller lacks permission for the operation.
+    PermissionDenied,
+
+    /// No object with the given name exists.
+    NotFound,
+
+    /// An object with the given name already exists.
+    AlreadyExists,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by OS abstraction
+    /// implementations can be converted to a set of generic errors upon
+    /// which generic code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// How long a blocking operation may wait.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Timeout {
+    /// Fail with `ErrorKind::WouldBlock` instead of waiting.
+    NoWait,
+    /// Wait at most this many milliseconds, rounded up to the OS tick.
+    Millis(u32),
+    /// Wait until the operation completes.
+    Forever,
+}
@@ -0,0 +1,31 @@
+pub use super::{Error, ErrorKind, ErrorType, Timeout};
+
+/// Notification bits, a lightweight signal distinct from message queues.
+///
+/// Each task or object owns a 32-bit word. Posting ORs bits into it and
+/// wakes waiters; bits stay set until cleared, so posts made before a wait
+/// are not lost. Typical uses are interrupt-to-task signalling and timers.
+pub trait Notifications: ErrorType {
+    /// Sets `bits` and wakes any waiter whose mask they intersect.
+    ///
+    /// Safe to call from interrupt context on implementations that support
+    /// it.
+    fn post(&self, bits: u32) -> Result<(), Self::Error>;
+
+    /// Waits until any bit in `mask` is set.
+    ///
+    /// # Parameters
+    /// - `mask`: Bits to wait for.
+    /// - `timeout`: How long to wait.
+    ///
+    /// # Returns
+    /// The set bits within `mask`, which are cleared before returning, or
+    /// `ErrorKind::TimedOut`.
+    fn wait_any(&self, mask: u32, timeout: Timeout) -> Result<u32, Self::Error>;
+
+    /// Clears `bits` without waiting.
+    ///
+    /// # Returns
+    /// The bits within `bits` that were set.
+    fn clear(&self, bits: u32) -> Result<u32, Self::Error>;
+}
@@ -0,0 +1,90 @@
+pub use super::{Error, ErrorKind, ErrorType};
+
+/// Cache attributes of a shared-memory mapping.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CacheAttr {
+    /// Cached write-back; writers call [`ShmRegion::flush`] and readers
+    /// [`ShmRegion::invalidate`] unless the platform is coherent.
+    WriteBack,
+    /// Cached write-through; readers still invalidate before reading.
+    WriteThrough,
+    /// Uncached, e.g. for buffers shared with DMA engines.
+    Uncached,
+}
+
+/// Access granted to an attached region.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ShmAccess {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// Named shared-memory regions for passing bulk data, e.g. firmware images
+/// or measurement logs, between tasks by reference.
+pub trait SharedMemory: ErrorType {
+    /// A mapped region.
+    type Region: ShmRegion<Error = Self::Error>;
+
+    /// Creates a region and maps it read-write.
+    ///
+    /// # Parameters
+    /// - `name`: Name other tasks attach by.
+    /// - `size`: Size in bytes, possibly rounded up to the page size.
+    /// - `cache`: Cache attributes of the mapping.
+    ///
+    /// # Returns
+    /// The mapped region, or `ErrorKind::AlreadyExists` if the name is taken.
+    fn create(
+        &mut self,
+        name: &str,
+        size: usize,
+        cache: CacheAttr,
+    ) -> Result<Self::Region, Self::Error>;
+
+    /// Maps an existing region.
+    ///
+    /// # Returns
+    /// The mapped region, or `ErrorKind::NotFound` if no region has `name`.
+    fn attach(&mut self, name: &str, access: ShmAccess) -> Result<Self::Region, Self::Error>;
+
+    /// Unmaps a region. The region is destroyed once no task has it mapped.
+    fn detach(&mut self, region: Self::Region) -> Result<(), Self::Error>;
+}
+
+/// A mapped shared-memory region.
+///
+/// Other tasks and DMA engines write the region concurrently, so it is never
+/// exposed as a Rust slice; data is copied in and out instead.
+pub trait ShmRegion: ErrorType {
+    /// Size of the region in bytes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` for an empty region.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies `buf.len()` bytes starting at `offset` out of the region.
+    ///
+    /// # Returns
+    /// `ErrorKind::InvalidArgument` if the range exceeds the region.
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Copies `data` into the region starting at `offset`.
+    ///
+    /// # Returns
+    /// `ErrorKind::PermissionDenied` for read-only mappings, or
+    /// `ErrorKind::InvalidArgument` if the range exceeds the region.
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Cache attributes of the mapping.
+    fn cache(&self) -> CacheAttr;
+
+    /// Writes cached data in `offset..offset + len` back to memory so other
+    /// tasks and devices observe it. No-op for uncached mappings.
+    fn flush(&mut self, offset: usize, len: usize) -> Result<(), Self::Error>;
+
+    /// Discards cached data in `offset..offset + len` so subsequent reads
+    /// observe writes by other tasks and devices. No-op for uncached mappings.
+    fn invalidate(&mut self, offset: usize, len: usize) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,87 @@
+use core::mem::ManuallyDrop;
+
+pub use super::{Error, ErrorKind, ErrorType, Timeout};
+
+/// Mutual exclusion lock, e.g. around one SHA engine shared by many clients.
+pub trait OsMutex: ErrorType {
+    /// Acquires the lock.
+    ///
+    /// # Parameters
+    /// - `timeout`: How long to wait for the current holder to release it.
+    ///
+    /// # Returns
+    /// `Ok(())` once held, or `ErrorKind::TimedOut` / `ErrorKind::WouldBlock`.
+    fn lock(&self, timeout: Timeout) -> Result<(), Self::Error>;
+
+    /// Releases the lock, which must be held by the caller.
+    fn unlock(&self) -> Result<(), Self::Error>;
+
+    /// Acquires the lock and returns a guard releasing it on drop.
+    fn lock_guard(&self, timeout: Timeout) -> Result<OsMutexGuard<'_, Self>, Self::Error>
+    where
+        Self: Sized,
+    {
+        self.lock(timeout)?;
+        Ok(OsMutexGuard { mutex: self })
+    }
+}
+
+/// Held [`OsMutex`], released on drop.
+#[derive(Debug)]
+pub struct OsMutexGuard<'a, M: OsMutex> {
+    mutex: &'a M,
+}
+
+impl<M: OsMutex> OsMutexGuard<'_, M> {
+    /// Releases the lock, returning any error instead of discarding it.
+    pub fn unlock(self) -> Result<(), M::Error> {
+        let this = ManuallyDrop::new(self);
+        this.mutex.unlock()
+    }
+}
+
+impl<M: OsMutex> Drop for OsMutexGuard<'_, M> {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop; `unlock` reports them.
+        let _ = self.mutex.unlock();
+    }
+}
+
+/// Counting semaphore.
+pub trait OsSemaphore: ErrorType {
+    /// Takes one unit, waiting while the count is zero.
+    ///
+    /// # Parameters
+    /// - `timeout`: How long to wait for a unit.
+    fn acquire(&self, timeout: Timeout) -> Result<(), Self::Error>;
+
+    /// Gives back one unit, waking one waiter.
+    ///
+    /// Safe to call from interrupt context on implementations that support
+    /// it.
+    fn release(&self) -> Result<(), Self::Error>;
+
+    /// Returns the number of units currently available.
+    fn count(&self) -> u32;
+}
+
+/// Manual-reset event flag for waiting on a condition, e.g. an engine
+/// becoming ready.
+///
+/// Once set, the flag wakes every waiter and stays set until reset.
+pub trait OsEventFlag: ErrorType {
+    /// Sets the flag, waking every waiter.
+    fn set(&self) -> Result<(), Self::Error>;
+
+    /// Clears the flag.
+    fn reset(&self) -> Result<(), Self::Error>;
+
+    /// Waits until the flag is set.
+    ///
+    /// # Parameters
+    /// - `timeout`: How long to wait.
+    fn wait(&self, timeout: Timeout) -> Result<(), Self::Error>;
+
+    /// Returns `true` if the flag is set.
+    fn is_set(&self) -> bool;
+}
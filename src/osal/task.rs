@@ -0,0 +1,55 @@
+pub use super::{Error, ErrorKind, ErrorType, Timeout};
+
+/// Task priority; larger values are more urgent.
+///
+/// Implementations map the value onto the OS range, e.g. inverted for
+/// kernels where lower numbers preempt higher ones.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Priority(pub u8);
+
+/// Parameters of a new task.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TaskConfig {
+    /// Name for debugging; may be truncated.
+    pub name: &'static str,
+    pub priority: Priority,
+    /// Stack size in bytes; implementations may round it up.
+    pub stack_size: usize,
+}
+
+/// Creation and scheduling of tasks, so driver services run unchanged on
+/// host threads and RTOS tasks.
+pub trait TaskControl: ErrorType {
+    /// Handle of a spawned task.
+    type Handle;
+
+    /// Starts a task running `entry(arg)`.
+    ///
+    /// # Parameters
+    /// - `config`: Name, priority and stack size.
+    /// - `entry`: Task body; the task ends when it returns.
+    /// - `arg`: Argument passed to `entry`, e.g. an index into a static table.
+    ///
+    /// # Returns
+    /// The handle of the new task, or an error.
+    fn spawn(
+        &mut self,
+        config: TaskConfig,
+        entry: fn(usize),
+        arg: usize,
+    ) -> Result<Self::Handle, Self::Error>;
+
+    /// Changes the priority of a task.
+    fn set_priority(&mut self, task: &Self::Handle, priority: Priority) -> Result<(), Self::Error>;
+
+    /// Waits for a task to end and releases its resources.
+    ///
+    /// # Parameters
+    /// - `task`: The task to wait for.
+    /// - `timeout`: How long to wait; on `ErrorKind::TimedOut` the task
+    ///   keeps running and can be joined again.
+    fn join(&mut self, task: &Self::Handle, timeout: Timeout) -> Result<(), Self::Error>;
+
+    /// Lets other ready tasks of the same priority run.
+    fn yield_now(&self);
+}
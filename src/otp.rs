@@ -0,0 +1,305 @@
+//! One-time programmable memory (OTP, eFuse).
+
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+
+use crate::capability::ProvisioningCap;
+use crate::contract::ContractVersion;
+use crate::units::WordOffset;
+
+/// Error kind.
+///
+/// This represents a common set of OTP errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The OTP controller is busy.
+    Busy,
+
+    /// The address is outside the OTP array.
+    OutOfBounds,
+
+    /// A word holds more bit errors than its ECC can correct.
+    EccUncorrectable,
+
+    /// Programming failed or did not read back as written.
+    ProgramError,
+
+    /// The requested lifecycle transition is not allowed from the current state.
+    InvalidTransition,
+
+    /// A monotonic counter is already at its maximum value.
+    CounterExhausted,
+
+    /// A failed location could not be remapped because no spare rows are left.
+    RepairExhausted,
+
+    /// Any other error.
+    Other,
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ErrorKind::Busy => "controller busy",
+            ErrorKind::OutOfBounds => "address out of bounds",
+            ErrorKind::EccUncorrectable => "uncorrectable ECC error",
+            ErrorKind::ProgramError => "programming failed",
+            ErrorKind::InvalidTransition => "lifecycle transition not allowed",
+            ErrorKind::CounterExhausted => "counter exhausted",
+            ErrorKind::RepairExhausted => "no spare rows left",
+            ErrorKind::Other => "other error",
+        })
+    }
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// ECC status of one OTP word.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EccStatus {
+    /// No bit errors.
+    Clean,
+    /// Bit errors were corrected; the word reads correctly but is degrading.
+    Corrected,
+    /// The word holds more bit errors than the ECC can correct.
+    Uncorrectable,
+}
+
+/// Error correction totals since the counters were last cleared.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct EccCounters {
+    /// Reads in which bit errors were corrected.
+    pub corrected: u32,
+    /// Reads that failed with uncorrectable errors.
+    pub uncorrectable: u32,
+}
+
+/// ECC reporting of OTP arrays protected by error-correcting codes.
+///
+/// Lets health monitoring notice fuses degrading before reads fail.
+pub trait OtpEcc: ErrorType {
+    /// Returns the ECC status of a word, without counting it as a read.
+    ///
+    /// # Parameters
+    ///
+    /// - `word`: Offset of the word in the OTP array.
+    fn ecc_status(&self, word: WordOffset) -> Result<EccStatus, Self::Error>;
+
+    /// Returns the correction counters.
+    fn ecc_counters(&self) -> Result<EccCounters, Self::Error>;
+
+    /// Resets the correction counters to zero.
+    fn clear_ecc_counters(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Programming session of controllers that must be unlocked for programming,
+/// e.g. by raising the programming voltage or writing an unlock key.
+pub trait OtpSession: ErrorType {
+    /// Opens a programming session.
+    fn begin_session(&mut self) -> Result<(), Self::Error>;
+
+    /// Closes the programming session and relocks the controller.
+    fn end_session(&mut self) -> Result<(), Self::Error>;
+
+    /// Opens a session that is closed when the returned guard is dropped.
+    ///
+    /// Early returns with `?` then cannot leave the controller unlocked. Use
+    /// [`OtpSessionGuard::end`] to observe errors from closing the session.
+    fn session(&mut self) -> Result<OtpSessionGuard<'_, Self>, Self::Error>
+    where
+        Self: Sized,
+    {
+        self.begin_session()?;
+        Ok(OtpSessionGuard { otp: self })
+    }
+
+    /// Runs `f` within a session, closing it whatever `f` returns.
+    ///
+    /// # Returns
+    ///
+    /// The result of `f`, or the error from closing the session if `f`
+    /// succeeded.
+    fn with_session<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<R, Self::Error>,
+    ) -> Result<R, Self::Error>
+    where
+        Self: Sized,
+    {
+        let mut session = self.session()?;
+        let result = f(&mut session);
+        let ended = session.end();
+        let value = result?;
+        ended.map(|()| value)
+    }
+}
+
+/// Open OTP session, closed on drop.
+///
+/// Dereferences to the controller so it can be programmed while the guard
+/// is held.
+#[derive(Debug)]
+pub struct OtpSessionGuard<'a, T: OtpSession> {
+    otp: &'a mut T,
+}
+
+impl<T: OtpSession> OtpSessionGuard<'_, T> {
+    /// Closes the session, returning any error instead of discarding it.
+    pub fn end(self) -> Result<(), T::Error> {
+        let mut this = ManuallyDrop::new(self);
+        this.otp.end_session()
+    }
+}
+
+impl<T: OtpSession> Deref for OtpSessionGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.otp
+    }
+}
+
+impl<T: OtpSession> DerefMut for OtpSessionGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.otp
+    }
+}
+
+impl<T: OtpSession> Drop for OtpSessionGuard<'_, T> {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop; `end` reports them.
+        let _ = self.otp.end_session();
+    }
+}
+
+/// Device lifecycle encoded in fuses, e.g. blank, provisioned, production
+/// and RMA.
+///
+/// States and their order are SoC specific, so the state is an associated
+/// type. Transitions are irreversible and require a [`ProvisioningCap`].
+pub trait OtpLifecycle: ErrorType {
+    /// Lifecycle state.
+    type LifecycleState: Copy + Eq + core::fmt::Debug;
+
+    /// Returns the current lifecycle state.
+    fn current_state(&self) -> Result<Self::LifecycleState, Self::Error>;
+
+    /// Returns `true` if the device may move from `from` to `to`.
+    fn is_transition_allowed(&self, from: Self::LifecycleState, to: Self::LifecycleState) -> bool;
+
+    /// Returns `true` if the device may move from its current state to `target`.
+    fn can_advance_to(&self, target: Self::LifecycleState) -> Result<bool, Self::Error> {
+        Ok(self.is_transition_allowed(self.current_state()?, target))
+    }
+
+    /// Moves the device to `target` by programming the lifecycle fuses.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: The state to enter.
+    /// - `cap`: Provisioning capability authorizing the change.
+    ///
+    /// # Returns
+    ///
+    /// An error of kind `ErrorKind::InvalidTransition` if the transition is
+    /// not allowed from the current state.
+    fn advance_state(
+        &mut self,
+        target: Self::LifecycleState,
+        cap: &ProvisioningCap,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Identifier of a monotonic counter.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CounterId(pub u16);
+
+/// Monotonic counters for anti-rollback, e.g. secure-boot version counters.
+///
+/// Counters only ever increase. They are typically backed by OTP bit
+/// strings, one fuse per increment, or by flash.
+pub trait MonotonicCounter: ErrorType {
+    /// Returns the current value of a counter.
+    fn read_counter(&self, id: CounterId) -> Result<u32, Self::Error>;
+
+    /// Increments a counter by one.
+    ///
+    /// # Returns
+    ///
+    /// The new value, or an error of kind `ErrorKind::CounterExhausted` if
+    /// the counter is already at [`max_value`](Self::max_value).
+    fn increment(&mut self, id: CounterId) -> Result<u32, Self::Error>;
+
+    /// Returns the largest value a counter can hold.
+    fn max_value(&self, id: CounterId) -> Result<u32, Self::Error>;
+}
+
+/// Spare rows of an OTP array with redundancy.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct SpareCapacity {
+    /// Spare rows provided by the array.
+    pub total: u32,
+    /// Spare rows already used for repairs.
+    pub used: u32,
+}
+
+impl SpareCapacity {
+    /// Spare rows still available.
+    pub const fn remaining(&self) -> u32 {
+        self.total.saturating_sub(self.used)
+    }
+}
+
+/// Repair reporting of fuse macros that remap failed bits to spare rows.
+///
+/// Intended for manufacturing telemetry.
+pub trait OtpRedundancy: ErrorType {
+    /// Lists the words that were remapped to spare rows.
+    ///
+    /// # Parameters
+    ///
+    /// - `out`: Receives the offsets of the repaired words.
+    ///
+    /// # Returns
+    ///
+    /// The total number of repaired words, which may exceed `out.len()`;
+    /// only the first `out.len()` are written.
+    fn repaired_locations(&self, out: &mut [WordOffset]) -> Result<usize, Self::Error>;
+
+    /// Returns the spare capacity of the array.
+    fn spare_capacity(&self) -> Result<SpareCapacity, Self::Error>;
+}
@@ -0,0 +1,365 @@
+//! Vendor-neutral OTP image container.
+//!
+//! Manufacturing tools build an image once and firmware parses and programs
+//! it, so both sides share one format. All integers are little endian.
+//!
+//! | Field                      | Size  |
+//! |----------------------------|-------|
+//! | magic `"OTPI"`             | 4     |
+//! | format version             | u16   |
+//! | section count              | u16   |
+//! | chip id                    | u32   |
+//! | minimum chip revision      | u16   |
+//! | maximum chip revision      | u16   |
+//! | total image length         | u32   |
+//! | CRC-32 of the whole image  | u32   |
+//!
+//! The CRC (IEEE 802.3) is computed with the CRC field zeroed. Each section
+//! follows as a 12-byte header (region u16, flags u16, byte offset in the
+//! region u32, data length u32) and its data, padded to a multiple of 4.
+
+use crate::otp::ErrorType;
+use crate::units::ByteOffset;
+
+/// Image magic.
+pub const MAGIC: [u8; 4] = *b"OTPI";
+
+/// Format version written and accepted by this module.
+pub const VERSION: u16 = 1;
+
+/// Size of the image header in bytes.
+pub const HEADER_SIZE: usize = 24;
+
+/// Size of a section header in bytes.
+pub const SECTION_HEADER_SIZE: usize = 12;
+
+const CRC_OFFSET: usize = 20;
+
+/// Reasons an image is rejected by [`OtpImage::parse`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ImageError {
+    /// The image does not start with [`MAGIC`].
+    BadMagic,
+    /// The format version is not [`VERSION`].
+    UnsupportedVersion,
+    /// The image is shorter than its headers declare.
+    Truncated,
+    /// The CRC does not match the image contents.
+    ChecksumMismatch,
+    /// The section headers do not add up to the declared image length.
+    Malformed,
+}
+
+/// Image header fields.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ImageHeader {
+    pub version: u16,
+    pub section_count: u16,
+    pub chip_id: u32,
+    pub min_chip_revision: u16,
+    pub max_chip_revision: u16,
+    pub total_len: u32,
+}
+
+/// Data to program into one OTP region.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OtpSection<'a> {
+    /// Implementation-defined region number, e.g. data, configuration or strap.
+    pub region: u16,
+    /// Implementation-defined flags, e.g. whether to lock the region afterwards.
+    pub flags: u16,
+    /// Offset of the data within the region.
+    pub offset: ByteOffset,
+    pub data: &'a [u8],
+}
+
+/// A parsed, checksum-verified OTP image.
+#[derive(Debug, Copy, Clone)]
+pub struct OtpImage<'a> {
+    header: ImageHeader,
+    sections: &'a [u8],
+}
+
+impl<'a> OtpImage<'a> {
+    /// Parses and validates an image.
+    ///
+    /// Every section header is checked here, so iterating the sections
+    /// afterwards cannot fail.
+    pub fn parse(image: &'a [u8]) -> Result<Self, ImageError> {
+        let header = image.get(..HEADER_SIZE).ok_or(ImageError::Truncated)?;
+        if header[..4] != MAGIC {
+            return Err(ImageError::BadMagic);
+        }
+        let header = ImageHeader {
+            version: u16_at(header, 4),
+            section_count: u16_at(header, 6),
+            chip_id: u32_at(header, 8),
+            min_chip_revision: u16_at(header, 12),
+            max_chip_revision: u16_at(header, 14),
+            total_len: u32_at(header, 16),
+        };
+        if header.version != VERSION {
+            return Err(ImageError::UnsupportedVersion);
+        }
+        // The declared length covers the header, which holds the CRC.
+        if (header.total_len as usize) < HEADER_SIZE {
+            return Err(ImageError::Malformed);
+        }
+        let image = image
+            .get(..header.total_len as usize)
+            .ok_or(ImageError::Truncated)?;
+        if crc32(image) != u32_at(image, CRC_OFFSET) {
+            return Err(ImageError::ChecksumMismatch);
+        }
+
+        let sections = &image[HEADER_SIZE..];
+        let mut rest = sections;
+        for _ in 0..header.section_count {
+            let (_, tail) = split_section(rest).ok_or(ImageError::Malformed)?;
+            rest = tail;
+        }
+        if !rest.is_empty() {
+            return Err(ImageError::Malformed);
+        }
+        Ok(Self { header, sections })
+    }
+
+    pub fn header(&self) -> &ImageHeader {
+        &self.header
+    }
+
+    /// Returns `true` if the image targets the given chip and revision.
+    pub fn is_compatible(&self, chip_id: u32, revision: u16) -> bool {
+        self.header.chip_id == chip_id
+            && (self.header.min_chip_revision..=self.header.max_chip_revision).contains(&revision)
+    }
+
+    /// Iterates over the sections in image order.
+    pub fn sections(&self) -> Sections<'a> {
+        Sections {
+            rest: self.sections,
+        }
+    }
+}
+
+/// Iterator over the sections of an [`OtpImage`].
+#[derive(Debug, Clone)]
+pub struct Sections<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = OtpSection<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (section, rest) = split_section(self.rest)?;
+        self.rest = rest;
+        Some(section)
+    }
+}
+
+/// Programs the sections of an OTP image.
+pub trait ProgramOtpImage: ErrorType {
+    /// Programs one section.
+    fn program_section(&mut self, section: &OtpSection<'_>) -> Result<(), Self::Error>;
+
+    /// Programs every section in image order, stopping at the first error.
+    ///
+    /// Compatibility with the chip is the caller's responsibility, see
+    /// [`OtpImage::is_compatible`].
+    fn program_image(&mut self, image: &OtpImage<'_>) -> Result<(), Self::Error> {
+        image
+            .sections()
+            .try_for_each(|section| self.program_section(&section))
+    }
+}
+
+/// CRC-32 (IEEE 802.3) of an image, with the CRC field taken as zero.
+///
+/// Image builders store this value at byte offset 20 of the header.
+pub fn crc32(image: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for (i, &b) in image.iter().enumerate() {
+        let b = if (CRC_OFFSET..CRC_OFFSET + 4).contains(&i) {
+            0
+        } else {
+            b
+        };
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn split_section(bytes: &[u8]) -> Option<(OtpSection<'_>, &[u8])> {
+    let header = bytes.get(..SECTION_HEADER_SIZE)?;
+    let len = u32_at(header, 8) as usize;
+    let padded = len.checked_next_multiple_of(4)?;
+    let end = SECTION_HEADER_SIZE.checked_add(padded)?;
+    let body = bytes.get(SECTION_HEADER_SIZE..end)?;
+    let section = OtpSection {
+        region: u16_at(header, 0),
+        flags: u16_at(header, 2),
+        offset: ByteOffset(u32_at(header, 4) as usize),
+        data: &body[..len],
+    };
+    Some((section, &bytes[end..]))
+}
+
+// Callers pass slices whose length has already been checked.
+fn u16_at(buf: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([buf[at], buf[at + 1]])
+}
+
+fn u32_at(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header with the given declared length, zero sections and a valid CRC
+    /// over the first `total_len` bytes when that covers the header.
+    fn image_with_len(total_len: u32) -> [u8; HEADER_SIZE] {
+        let mut image = [0u8; HEADER_SIZE];
+        image[..4].copy_from_slice(&MAGIC);
+        image[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        image[16..20].copy_from_slice(&total_len.to_le_bytes());
+        if total_len as usize >= HEADER_SIZE {
+            let crc = crc32(&image);
+            image[CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+        }
+        image
+    }
+
+    /// Writes an image for chip 0x1234 revisions 2..=5 into `out`, declaring
+    /// `count` sections, and returns its length.
+    fn build(out: &mut [u8], count: u16, sections: &[(u16, u16, u32, &[u8])]) -> usize {
+        out[..4].copy_from_slice(&MAGIC);
+        out[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        out[6..8].copy_from_slice(&count.to_le_bytes());
+        out[8..12].copy_from_slice(&0x1234u32.to_le_bytes());
+        out[12..14].copy_from_slice(&2u16.to_le_bytes());
+        out[14..16].copy_from_slice(&5u16.to_le_bytes());
+        let mut at = HEADER_SIZE;
+        for &(region, flags, offset, data) in sections {
+            out[at..at + 2].copy_from_slice(&region.to_le_bytes());
+            out[at + 2..at + 4].copy_from_slice(&flags.to_le_bytes());
+            out[at + 4..at + 8].copy_from_slice(&offset.to_le_bytes());
+            out[at + 8..at + 12].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            at += SECTION_HEADER_SIZE;
+            out[at..at + data.len()].copy_from_slice(data);
+            out[at + data.len()..at + data.len().next_multiple_of(4)].fill(0);
+            at += data.len().next_multiple_of(4);
+        }
+        out[16..20].copy_from_slice(&(at as u32).to_le_bytes());
+        let crc = crc32(&out[..at]);
+        out[CRC_OFFSET..CRC_OFFSET + 4].copy_from_slice(&crc.to_le_bytes());
+        at
+    }
+
+    const SECTIONS: [(u16, u16, u32, &[u8]); 3] = [
+        (0, 1, 0x40, b"\x01\x02\x03\x04"),
+        (2, 0, 0x08, b"abcde"),
+        (1, 0, 0, b""),
+    ];
+
+    #[test]
+    fn crc32_known_answer() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn sections_parse_in_order() {
+        // Bytes past the declared length are not part of the image.
+        let mut image = [0xA5u8; 128];
+        let len = build(&mut image, 3, &SECTIONS);
+        assert_eq!(len, HEADER_SIZE + 3 * SECTION_HEADER_SIZE + 4 + 8);
+
+        let parsed = OtpImage::parse(&image).unwrap();
+        assert_eq!(
+            *parsed.header(),
+            ImageHeader {
+                version: VERSION,
+                section_count: 3,
+                chip_id: 0x1234,
+                min_chip_revision: 2,
+                max_chip_revision: 5,
+                total_len: len as u32,
+            }
+        );
+        let mut sections = parsed.sections();
+        for (region, flags, offset, data) in SECTIONS {
+            assert_eq!(
+                sections.next(),
+                Some(OtpSection {
+                    region,
+                    flags,
+                    offset: ByteOffset(offset as usize),
+                    data,
+                })
+            );
+        }
+        assert_eq!(sections.next(), None);
+
+        assert!(parsed.is_compatible(0x1234, 2));
+        assert!(parsed.is_compatible(0x1234, 5));
+        assert!(!parsed.is_compatible(0x1234, 6));
+        assert!(!parsed.is_compatible(0x1235, 3));
+    }
+
+    #[test]
+    fn section_count_mismatch_is_malformed() {
+        let mut image = [0u8; 128];
+        for count in [2, 4] {
+            build(&mut image, count, &SECTIONS);
+            assert_eq!(
+                OtpImage::parse(&image).map(|_| ()),
+                Err(ImageError::Malformed)
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted_image_fails_checksum() {
+        let mut image = [0u8; 128];
+        let len = build(&mut image, 3, &SECTIONS);
+        image[len - 1] ^= 0x01;
+        assert_eq!(
+            OtpImage::parse(&image).map(|_| ()),
+            Err(ImageError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn total_len_shorter_than_header_is_malformed() {
+        for total_len in [0, 1, 23] {
+            let image = image_with_len(total_len);
+            assert_eq!(
+                OtpImage::parse(&image).map(|_| ()),
+                Err(ImageError::Malformed)
+            );
+        }
+    }
+
+    #[test]
+    fn header_only_image_parses() {
+        let image = image_with_len(HEADER_SIZE as u32);
+        let parsed = OtpImage::parse(&image).unwrap();
+        assert_eq!(parsed.header().total_len, HEADER_SIZE as u32);
+        assert_eq!(parsed.sections().count(), 0);
+    }
+
+    #[test]
+    fn total_len_beyond_buffer_is_truncated() {
+        let image = image_with_len(HEADER_SIZE as u32 + 4);
+        assert_eq!(
+            OtpImage::parse(&image).map(|_| ()),
+            Err(ImageError::Truncated)
+        );
+    }
+}
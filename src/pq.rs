@@ -0,0 +1,186 @@
+//! Post-quantum signature verification.
+//!
+//! Secure boot only needs to verify signatures, so only verification is
+//! covered: the stateless lattice scheme ML-DSA (FIPS 204) and the stateful
+//! hash-based schemes LMS and HSS (RFC 8554, NIST SP 800-208). Keys and
+//! signatures are passed in their standard byte encodings.
+
+use crate::contract::ContractVersion;
+use crate::ecdsa::HashMarker;
+
+/// Error kind.
+///
+/// This represents a common set of signature verification errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// The signature does not verify.
+    InvalidSignature,
+
+    /// The public key encoding is malformed.
+    InvalidPublicKey,
+
+    /// The parameter set or pre-hash algorithm is not supported.
+    UnsupportedParameterSet,
+
+    /// A streaming operation was driven out of order.
+    InvalidState,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// ML-DSA parameter set.
+pub trait MlDsaParams {
+    /// Size of an encoded public key in bytes.
+    const PUBLIC_KEY_SIZE: usize;
+    /// Size of an encoded signature in bytes.
+    const SIGNATURE_SIZE: usize;
+}
+
+/// ML-DSA-44 (NIST security category 2).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MlDsa44;
+
+/// ML-DSA-65 (NIST security category 3).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MlDsa65;
+
+/// ML-DSA-87 (NIST security category 5).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MlDsa87;
+
+impl MlDsaParams for MlDsa44 {
+    const PUBLIC_KEY_SIZE: usize = 1312;
+    const SIGNATURE_SIZE: usize = 2420;
+}
+
+impl MlDsaParams for MlDsa65 {
+    const PUBLIC_KEY_SIZE: usize = 1952;
+    const SIGNATURE_SIZE: usize = 3309;
+}
+
+impl MlDsaParams for MlDsa87 {
+    const PUBLIC_KEY_SIZE: usize = 2592;
+    const SIGNATURE_SIZE: usize = 4627;
+}
+
+/// Trait for ML-DSA (FIPS 204) signature verification.
+pub trait MlDsaVerify<P: MlDsaParams>: ErrorType {
+    /// Verifies a pure ML-DSA signature over `message`.
+    ///
+    /// # Parameters
+    /// - `public_key`: Encoded public key, `P::PUBLIC_KEY_SIZE` bytes.
+    /// - `message`: The signed message.
+    /// - `context`: Context string, at most 255 bytes; empty if unused.
+    /// - `signature`: Encoded signature, `P::SIGNATURE_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn verify(
+        &mut self,
+        public_key: &[u8],
+        message: &[u8],
+        context: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Verifies a HashML-DSA signature over a message digest.
+    ///
+    /// Large images are streamed through any digest engine implementing the
+    /// crate's digest traits, and only the resulting digest is passed here.
+    ///
+    /// # Parameters
+    /// - `public_key`: Encoded public key, `P::PUBLIC_KEY_SIZE` bytes.
+    /// - `message_hash`: Digest of the message computed with hash `H`.
+    /// - `context`: Context string, at most 255 bytes; empty if unused.
+    /// - `signature`: Encoded signature, `P::SIGNATURE_SIZE` bytes.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn verify_prehashed<H: HashMarker>(
+        &mut self,
+        public_key: &[u8],
+        message_hash: &[u8],
+        context: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Trait for LMS and HSS (RFC 8554) signature verification.
+///
+/// The parameter set is encoded in the public key and signature, so one
+/// implementation covers every LMS/LM-OTS type it supports. An HSS public key
+/// with a single level is equivalent to an LMS public key.
+pub trait HssVerify: ErrorType {
+    /// Verifies an HSS signature over `message`.
+    ///
+    /// # Parameters
+    /// - `public_key`: Encoded HSS public key.
+    /// - `message`: The signed message.
+    /// - `signature`: Encoded HSS signature.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn verify(
+        &mut self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Streaming variant of [`HssVerify`] for messages too large to buffer.
+///
+/// LM-OTS hashes the signature randomizer before the message, so the
+/// signature is supplied when the stream is started.
+pub trait HssVerifyStream: ErrorType {
+    /// Starts verification of `signature` under `public_key`.
+    fn begin(&mut self, public_key: &[u8], signature: &[u8]) -> Result<(), Self::Error>;
+
+    /// Absorbs the next part of the message.
+    fn update(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Completes verification.
+    ///
+    /// # Returns
+    /// A result indicating whether the signature is valid, or an error.
+    fn finish(&mut self) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,138 @@
+//! Bounds, overlap and alignment checks for address ranges.
+//!
+//! Ranges are half-open `start..end` byte (or word) ranges. All helpers are
+//! overflow-safe: a range whose end does not fit in `usize` is rejected
+//! rather than wrapped.
+
+use core::ops::Range;
+
+/// Reason a range failed validation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RangeError {
+    /// `start + len` overflows.
+    Overflow,
+    /// The range is not inside the permitted bounds.
+    OutOfBounds,
+    /// The start or length is not a multiple of the required alignment.
+    Misaligned,
+    /// Two ranges share at least one address.
+    Overlap,
+}
+
+/// Builds the range `start..start + len`.
+///
+/// ```
+/// use peripheral_traits::range_check::span;
+///
+/// assert_eq!(span(0x100, 0x20), Some(0x100..0x120));
+/// assert_eq!(span(usize::MAX, 1), None);
+/// ```
+pub fn span(start: usize, len: usize) -> Option<Range<usize>> {
+    Some(start..start.checked_add(len)?)
+}
+
+/// Returns `true` if the two ranges share at least one address.
+///
+/// Empty ranges never overlap anything.
+///
+/// ```
+/// use peripheral_traits::range_check::overlaps;
+///
+/// assert!(overlaps(&(0..10), &(9..12)));
+/// assert!(!overlaps(&(0..10), &(10..12)));
+/// assert!(!overlaps(&(4..4), &(0..10)));
+/// ```
+pub fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    !a.is_empty() && !b.is_empty() && a.start < b.end && b.start < a.end
+}
+
+/// Returns `true` if `inner` lies entirely within `outer`.
+///
+/// ```
+/// use peripheral_traits::range_check::contains;
+///
+/// assert!(contains(&(0..16), &(4..16)));
+/// assert!(contains(&(0..16), &(16..16)));
+/// assert!(!contains(&(0..16), &(8..17)));
+/// ```
+pub fn contains(outer: &Range<usize>, inner: &Range<usize>) -> bool {
+    inner.start >= outer.start && inner.end <= outer.end && inner.start <= inner.end
+}
+
+/// Returns `true` if `value` is a multiple of `align`.
+///
+/// An alignment of zero only accepts zero.
+///
+/// ```
+/// use peripheral_traits::range_check::is_aligned;
+///
+/// assert!(is_aligned(0x1000, 0x1000));
+/// assert!(!is_aligned(0x1004, 0x1000));
+/// assert!(is_aligned(3, 1));
+/// ```
+pub fn is_aligned(value: usize, align: usize) -> bool {
+    value.is_multiple_of(align)
+}
+
+/// Validates that `start..start + len` is inside `0..capacity`.
+///
+/// ```
+/// use peripheral_traits::range_check::{check_bounds, RangeError};
+///
+/// assert_eq!(check_bounds(0x80, 0x80, 0x100), Ok(0x80..0x100));
+/// assert_eq!(check_bounds(0x80, 0x81, 0x100), Err(RangeError::OutOfBounds));
+/// assert_eq!(check_bounds(usize::MAX, 2, 0x100), Err(RangeError::Overflow));
+/// ```
+pub fn check_bounds(start: usize, len: usize, capacity: usize) -> Result<Range<usize>, RangeError> {
+    let range = span(start, len).ok_or(RangeError::Overflow)?;
+    if range.end > capacity {
+        return Err(RangeError::OutOfBounds);
+    }
+    Ok(range)
+}
+
+/// Validates bounds like [`check_bounds`] and that both `start` and `len`
+/// are multiples of `align`.
+///
+/// ```
+/// use peripheral_traits::range_check::{check_aligned, RangeError};
+///
+/// assert_eq!(check_aligned(0x1000, 0x2000, 0x1000, 0x4000), Ok(0x1000..0x3000));
+/// assert_eq!(check_aligned(0x1000, 0x800, 0x1000, 0x4000), Err(RangeError::Misaligned));
+/// assert_eq!(check_aligned(0x3000, 0x2000, 0x1000, 0x4000), Err(RangeError::OutOfBounds));
+/// ```
+pub fn check_aligned(
+    start: usize,
+    len: usize,
+    align: usize,
+    capacity: usize,
+) -> Result<Range<usize>, RangeError> {
+    if !is_aligned(start, align) || !is_aligned(len, align) {
+        return Err(RangeError::Misaligned);
+    }
+    check_bounds(start, len, capacity)
+}
+
+/// Finds the first pair of overlapping ranges, e.g. in a partition table.
+///
+/// # Returns
+///
+/// The indices `(i, j)` with `i < j` of the first overlapping pair, or `None`
+/// if all ranges are disjoint.
+///
+/// ```
+/// use peripheral_traits::range_check::find_overlap;
+///
+/// assert_eq!(find_overlap(&[0..4, 4..8, 8..12]), None);
+/// assert_eq!(find_overlap(&[0..4, 8..12, 3..5]), Some((0, 2)));
+/// ```
+pub fn find_overlap(ranges: &[Range<usize>]) -> Option<(usize, usize)> {
+    for (i, a) in ranges.iter().enumerate() {
+        for (j, b) in ranges.iter().enumerate().skip(i + 1) {
+            if overlaps(a, b) {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,136 @@
+//! Entropy sources and true random number generators.
+
+use crate::contract::ContractVersion;
+
+/// Error kind.
+///
+/// This represents a common set of entropy source errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// Not enough entropy has been collected yet, e.g. right after reset.
+    NotReady,
+
+    /// A continuous or start-up health test (NIST SP 800-90B) failed.
+    HealthTestFailed,
+
+    /// General hardware failure.
+    HardwareFailure,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// Source of cryptographically secure random bytes, e.g. a TRNG peripheral
+/// or a DRBG seeded from one.
+pub trait EntropySource: ErrorType {
+    /// Fills `dest` with random bytes.
+    ///
+    /// # Parameters
+    ///
+    /// - `dest`: The buffer to fill.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. On failure the contents of `dest` must not be used.
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Fills `dest` with random bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source fails; use `try_fill_bytes` where failures must be handled.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("entropy source failed: {:?}", e);
+        }
+    }
+}
+
+/// Health monitoring of a true random number generator.
+pub trait TrngHealth: EntropySource {
+    /// Runs the on-demand health tests of the noise source.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or `ErrorKind::HealthTestFailed`.
+    fn run_health_test(&mut self) -> Result<(), Self::Error>;
+
+    /// Estimated min-entropy of the raw noise source, in bits per 1024 bits of output.
+    fn entropy_estimate(&self) -> u16;
+}
+
+/// Bridges an [`EntropySource`] into `rand_core`, for APIs taking an
+/// `RngCore + CryptoRng`.
+#[cfg(feature = "rand_core")]
+pub struct RandCoreAdapter<T>(pub T);
+
+#[cfg(feature = "rand_core")]
+impl<T: EntropySource> rand_core::RngCore for RandCoreAdapter<T> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.0.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.0.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    /// Failures are reported as `rand_core::Error::CUSTOM_START` plus the
+    /// position of the error kind in [`ErrorKind`].
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest).map_err(|e| {
+            let code = rand_core::Error::CUSTOM_START + e.kind() as u32;
+            core::num::NonZeroU32::new(code)
+                .expect("CUSTOM_START is non-zero")
+                .into()
+        })
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl<T: EntropySource> rand_core::CryptoRng for RandCoreAdapter<T> {}
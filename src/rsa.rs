@@ -1,10 +1,62 @@
+use core::marker::PhantomData;
 use core::num::NonZeroU32;
 
-pub enum PaddingMode {
-    Pkcs1v15,
-    Pss,
+use crate::common::Zeroize;
+use crate::contract::ContractVersion;
+use crate::ecdsa::HashMarker;
+
+/// RSA padding scheme (RFC 8017), chosen as a type parameter like the hash
+/// of an ECDSA signature.
+pub trait RsaPadding {
+    /// Size of the hash used by the scheme and its MGF1, or `None` for
+    /// PKCS#1 v1.5, which has none.
+    fn hash_size() -> Option<usize>;
 }
 
+/// Padding usable with [`RsaSign`] and [`RsaVerify`].
+pub trait SignaturePadding: RsaPadding {}
+
+/// Padding usable with [`RsaEncrypt`] and [`RsaDecrypt`].
+pub trait EncryptionPadding: RsaPadding {}
+
+/// PKCS#1 v1.5 padding: RSASSA-PKCS1-v1_5 for signatures, and
+/// RSAES-PKCS1-v1_5 for encryption, for compatibility with existing peers
+/// only.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Pkcs1v15;
+
+impl RsaPadding for Pkcs1v15 {
+    fn hash_size() -> Option<usize> {
+        None
+    }
+}
+
+impl SignaturePadding for Pkcs1v15 {}
+
+impl EncryptionPadding for Pkcs1v15 {}
+
+/// RSASSA-PSS padding with hash and MGF1 hash `H`.
+pub struct Pss<H>(PhantomData<H>);
+
+impl<H: HashMarker> RsaPadding for Pss<H> {
+    fn hash_size() -> Option<usize> {
+        Some(H::size())
+    }
+}
+
+impl<H: HashMarker> SignaturePadding for Pss<H> {}
+
+/// RSAES-OAEP padding with hash and MGF1 hash `H` and an empty label.
+pub struct Oaep<H>(PhantomData<H>);
+
+impl<H: HashMarker> RsaPadding for Oaep<H> {
+    fn hash_size() -> Option<usize> {
+        Some(H::size())
+    }
+}
+
+impl<H: HashMarker> EncryptionPadding for Oaep<H> {}
+
 pub enum RsaSize {
     Size2048,
     Size3072,
@@ -12,10 +64,26 @@ pub enum RsaSize {
     Other(NonZeroU32),
 }
 
+impl RsaSize {
+    /// Size of the modulus, and of signatures and ciphertexts, in bytes.
+    pub fn modulus_bytes(&self) -> usize {
+        match self {
+            RsaSize::Size2048 => 256,
+            RsaSize::Size3072 => 384,
+            RsaSize::Size4096 => 512,
+            RsaSize::Other(bits) => bits.get().div_ceil(8) as usize,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum ErrorKind {
     InvalidLength,
     SignError,
     VerifyError,
+    EncryptError,
+    /// Decryption failed, including any padding check.
+    DecryptError,
 }
 
 pub trait Error: core::fmt::Debug {
@@ -27,6 +95,9 @@ pub trait Error: core::fmt::Debug {
     fn kind(&self) -> ErrorKind;
 }
 
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
 /// RSA error type trait.
 ///
 // This just defines the error type, to be used by the other  traits.
@@ -35,8 +106,17 @@ pub trait ErrorType {
     type Error: Error;
 }
 
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
 pub trait RsaKeys {
-    type PrivateKey;
+    type PrivateKey: Zeroize;
     type PublicKey;
 }
 
@@ -51,21 +131,60 @@ pub trait RsaKeyGen: ErrorType + RsaKeys {
 
 pub trait RsaSign: ErrorType + RsaKeys + RsaSignature {
 
-    fn sign(
+    fn sign<P: SignaturePadding>(
         &self,
         private_key: &Self::PrivateKey,
         message_digest: impl AsRef<[u8]>,
-        padding_mode: PaddingMode,
     ) -> Result<Self::Signature, Self::Error>;
 }
 
 pub trait RsaVerify: ErrorType + RsaKeys + RsaSignature {
 
-    fn verify(
+    fn verify<P: SignaturePadding>(
         &self,
         public_key: &Self::PublicKey,
         message_digest: impl AsRef<[u8]>,
-        padding_mode: PaddingMode,
         signature: &Self::Signature,
     ) -> Result<Self::Signature, Self::Error>;
 }
+
+/// Trait for RSA encryption.
+pub trait RsaEncrypt: ErrorType + RsaKeys {
+    /// Encrypts a message with the public key and padding `P`.
+    ///
+    /// # Parameters
+    /// - `public_key`: The public key to encrypt to.
+    /// - `message`: The message, e.g. a key to transport.
+    /// - `out`: Receives the ciphertext, the size of the modulus.
+    ///
+    /// # Returns
+    /// A result containing the ciphertext length, or an error.
+    fn encrypt<P: EncryptionPadding>(
+        &self,
+        public_key: &Self::PublicKey,
+        message: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Self::Error>;
+}
+
+/// Trait for RSA decryption.
+pub trait RsaDecrypt: ErrorType + RsaKeys {
+    /// Decrypts a ciphertext with the private key and padding `P`.
+    ///
+    /// Padding failures are reported as `ErrorKind::DecryptError`, without
+    /// distinguishing their cause, so callers cannot act as padding oracles.
+    ///
+    /// # Parameters
+    /// - `private_key`: The private key to decrypt with.
+    /// - `ciphertext`: The ciphertext, the size of the modulus.
+    /// - `out`: Receives the message.
+    ///
+    /// # Returns
+    /// A result containing the message length, or an error.
+    fn decrypt<P: EncryptionPadding>(
+        &self,
+        private_key: &Self::PrivateKey,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Self::Error>;
+}
@@ -0,0 +1,280 @@
+//! Software providers and helpers for the crate's known-answer tests.
+
+use core::convert::Infallible;
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use sha2::Digest as _;
+
+use crate::block_device::{self, BlockDevice, ProgramCapabilities, ReadOnlyBlockDevice};
+use crate::cipher::{self, BlockCipher, CipherInit, CipherOp, Ecb};
+use crate::digest::{Digest, ErrorType};
+use crate::ecdsa::HashMarker;
+use crate::units::ByteOffset;
+
+/// Decodes a hex string of exactly `N` bytes.
+pub fn hex<const N: usize>(s: &str) -> [u8; N] {
+    assert_eq!(s.len(), 2 * N, "hex string must encode {N} bytes");
+    let mut out = [0u8; N];
+    for (byte, pair) in out.iter_mut().zip(s.as_bytes().chunks(2)) {
+        let pair = core::str::from_utf8(pair).unwrap();
+        *byte = u8::from_str_radix(pair, 16).unwrap();
+    }
+    out
+}
+
+macro_rules! sha_provider {
+    ($name:ident, $marker:ident, $inner:ty, $size:expr, $block:expr) => {
+        /// Software digest provider for the known-answer tests.
+        #[derive(Default)]
+        pub struct $name(pub $inner);
+
+        impl ErrorType for $name {
+            type Error = Infallible;
+        }
+
+        impl Digest for $name {
+            type InitParams = ();
+
+            const OUTPUT_SIZE: usize = $size;
+
+            const BLOCK_SIZE: usize = $block;
+
+            fn init(_init_params: ()) -> Result<(), Infallible> {
+                Ok(())
+            }
+
+            fn update(&mut self, input: &mut [u8]) -> Result<(), Infallible> {
+                self.0.update(&*input);
+                Ok(())
+            }
+
+            fn reset(&mut self) -> Result<(), Infallible> {
+                self.0 = <$inner>::new();
+                Ok(())
+            }
+
+            fn finalize(&mut self, out: &mut [u8]) -> Result<(), Infallible> {
+                out[..$size].copy_from_slice(&self.0.finalize_reset());
+                Ok(())
+            }
+        }
+
+        /// Hash marker matching the provider.
+        pub struct $marker;
+
+        impl HashMarker for $marker {
+            fn size() -> usize {
+                $size
+            }
+        }
+    };
+}
+
+sha_provider!(Sha256, Sha256Hash, sha2::Sha256, 32, 64);
+sha_provider!(Sha512, Sha512Hash, sha2::Sha512, 64, 128);
+
+/// Software AES-ECB engine for the known-answer tests; the key length
+/// selects AES-128, AES-192 or AES-256.
+pub struct SoftAes;
+
+/// Keyed [`SoftAes`] operation.
+pub enum SoftAesOp {
+    Aes128(aes::Aes128),
+    Aes192(aes::Aes192),
+    Aes256(aes::Aes256),
+}
+
+impl cipher::ErrorType for SoftAes {
+    type Error = Infallible;
+}
+
+impl cipher::ErrorType for SoftAesOp {
+    type Error = Infallible;
+}
+
+impl<C: BlockCipher> CipherInit<C, Ecb> for SoftAes {
+    type Op<'a> = SoftAesOp;
+
+    fn init(
+        &mut self,
+        _cipher: C,
+        _mode: Ecb,
+        key: &[u8],
+        _iv: &[u8],
+    ) -> Result<SoftAesOp, Infallible> {
+        Ok(match key.len() {
+            16 => SoftAesOp::Aes128(KeyInit::new_from_slice(key).unwrap()),
+            24 => SoftAesOp::Aes192(KeyInit::new_from_slice(key).unwrap()),
+            32 => SoftAesOp::Aes256(KeyInit::new_from_slice(key).unwrap()),
+            len => panic!("no AES key of {len} bytes"),
+        })
+    }
+}
+
+impl CipherOp for SoftAesOp {
+    fn encrypt_in_place(&mut self, data: &mut [u8]) -> Result<(), Infallible> {
+        for block in data.chunks_exact_mut(16) {
+            let block = GenericArray::from_mut_slice(block);
+            match self {
+                SoftAesOp::Aes128(c) => c.encrypt_block(block),
+                SoftAesOp::Aes192(c) => c.encrypt_block(block),
+                SoftAesOp::Aes256(c) => c.encrypt_block(block),
+            }
+        }
+        Ok(())
+    }
+
+    fn decrypt_in_place(&mut self, data: &mut [u8]) -> Result<(), Infallible> {
+        for block in data.chunks_exact_mut(16) {
+            let block = GenericArray::from_mut_slice(block);
+            match self {
+                SoftAesOp::Aes128(c) => c.decrypt_block(block),
+                SoftAesOp::Aes192(c) => c.decrypt_block(block),
+                SoftAesOp::Aes256(c) => c.decrypt_block(block),
+            }
+        }
+        Ok(())
+    }
+
+    fn set_iv(&mut self, _iv: &[u8]) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Error returned by [`NorMock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NorMockError {
+    OutOfBounds,
+    Misaligned,
+    /// Power was cut by [`NorMock::cut_power_after`].
+    PowerLoss,
+}
+
+impl block_device::Error for NorMockError {
+    fn kind(&self) -> block_device::ErrorKind {
+        match self {
+            NorMockError::OutOfBounds | NorMockError::Misaligned => {
+                block_device::ErrorKind::OutOfBounds
+            }
+            NorMockError::PowerLoss => block_device::ErrorKind::ProgramError,
+        }
+    }
+}
+
+/// In-memory NOR flash of `N` bytes: programming only clears bits and
+/// erasing sets whole sectors back to `0xFF`.
+pub struct NorMock<const N: usize> {
+    pub data: [u8; N],
+    pub sector_size: usize,
+    pub program_size: usize,
+    pub caps: ProgramCapabilities,
+    /// Bytes left to program before power is cut; `None` for no cut.
+    budget: Option<usize>,
+}
+
+impl<const N: usize> NorMock<N> {
+    /// Erased device with `sector_size` byte sectors, 1-byte programs and
+    /// NOR program capabilities.
+    pub fn new(sector_size: usize) -> Self {
+        Self {
+            data: [0xFF; N],
+            sector_size,
+            program_size: 1,
+            caps: ProgramCapabilities {
+                partial_page: true,
+                bit_clearing: true,
+                overwrite: false,
+            },
+            budget: None,
+        }
+    }
+
+    /// Cuts power once `bytes` more bytes have been programmed. The program
+    /// running at that point is left torn and fails, as do all later ones
+    /// until [`Self::power_on`].
+    pub fn cut_power_after(&mut self, bytes: usize) {
+        self.budget = Some(bytes);
+    }
+
+    pub fn power_on(&mut self) {
+        self.budget = None;
+    }
+
+    fn range(&self, addr: usize, len: usize) -> Result<core::ops::Range<usize>, NorMockError> {
+        match addr.checked_add(len) {
+            Some(end) if end <= N => Ok(addr..end),
+            _ => Err(NorMockError::OutOfBounds),
+        }
+    }
+}
+
+impl<const N: usize> block_device::ErrorType for NorMock<N> {
+    type Error = NorMockError;
+}
+
+impl<const N: usize> ReadOnlyBlockDevice for NorMock<N> {
+    fn read_size(&self) -> usize {
+        1
+    }
+
+    fn read(&mut self, block_addr: ByteOffset, data: &mut [u8]) -> Result<(), NorMockError> {
+        let range = self.range(block_addr.get(), data.len())?;
+        data.copy_from_slice(&self.data[range]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> BlockDevice for NorMock<N> {
+    fn erase_size(&self) -> usize {
+        self.sector_size
+    }
+
+    fn erase(&mut self, block_addr: ByteOffset, size_in_bytes: usize) -> Result<(), NorMockError> {
+        let range = self.range(block_addr.get(), size_in_bytes)?;
+        if !range.start.is_multiple_of(self.sector_size)
+            || !size_in_bytes.is_multiple_of(self.sector_size)
+        {
+            return Err(NorMockError::Misaligned);
+        }
+        if self.budget == Some(0) {
+            return Err(NorMockError::PowerLoss);
+        }
+        self.data[range].fill(0xFF);
+        Ok(())
+    }
+
+    fn program_size(&self) -> usize {
+        self.program_size
+    }
+
+    fn program_capabilities(&self) -> ProgramCapabilities {
+        self.caps
+    }
+
+    fn program(&mut self, block_addr: ByteOffset, data: &[u8]) -> Result<(), NorMockError> {
+        let range = self.range(block_addr.get(), data.len())?;
+        if !range.start.is_multiple_of(self.program_size)
+            || !data.len().is_multiple_of(self.program_size)
+        {
+            return Err(NorMockError::Misaligned);
+        }
+        let applied = self
+            .budget
+            .map_or(data.len(), |budget| budget.min(data.len()));
+        for (cell, byte) in self.data[range].iter_mut().zip(&data[..applied]) {
+            *cell &= byte;
+        }
+        if let Some(budget) = &mut self.budget {
+            *budget -= applied;
+            if applied < data.len() {
+                return Err(NorMockError::PowerLoss);
+            }
+        }
+        Ok(())
+    }
+}
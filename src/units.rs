@@ -0,0 +1,75 @@
+//! Typed offsets and lengths.
+//!
+//! OTP memories are indexed by word while flash devices are indexed by byte.
+//! Wrapping both in distinct types turns mixing them up into a compile error;
+//! conversion is explicit and takes the word size in bytes.
+
+/// Offset in bytes from the start of a device or region.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ByteOffset(pub usize);
+
+/// Offset in words from the start of a device or region.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct WordOffset(pub usize);
+
+/// Length expressed as a number of words.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct WordCount(pub usize);
+
+impl ByteOffset {
+    /// Returns the raw byte offset.
+    pub const fn get(self) -> usize {
+        self.0
+    }
+
+    /// Offset advanced by `bytes`, or `None` on overflow.
+    pub const fn checked_add(self, bytes: usize) -> Option<Self> {
+        match self.0.checked_add(bytes) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+
+    /// Converts to a word offset, or `None` if not aligned to `word_size`.
+    pub const fn to_words(self, word_size: usize) -> Option<WordOffset> {
+        if word_size == 0 || !self.0.is_multiple_of(word_size) {
+            return None;
+        }
+        Some(WordOffset(self.0 / word_size))
+    }
+}
+
+impl WordOffset {
+    /// Returns the raw word offset.
+    pub const fn get(self) -> usize {
+        self.0
+    }
+
+    /// Offset advanced by `count` words, or `None` on overflow.
+    pub const fn checked_add(self, count: WordCount) -> Option<Self> {
+        match self.0.checked_add(count.0) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+
+    /// Converts to a byte offset for words of `word_size` bytes.
+    pub const fn to_bytes(self, word_size: usize) -> Option<ByteOffset> {
+        match self.0.checked_mul(word_size) {
+            Some(v) => Some(ByteOffset(v)),
+            None => None,
+        }
+    }
+}
+
+impl WordCount {
+    /// Returns the raw word count.
+    pub const fn get(self) -> usize {
+        self.0
+    }
+
+    /// Length in bytes for words of `word_size` bytes.
+    pub const fn to_bytes(self, word_size: usize) -> Option<usize> {
+        self.0.checked_mul(word_size)
+    }
+}
@@ -0,0 +1,128 @@
+use crate::common::{ConstantTimeEq, Zeroize};
+use crate::contract::ContractVersion;
+
+/// Size in bytes of X25519 keys and shared secrets.
+pub const KEY_SIZE: usize = 32;
+
+/// Error kind.
+///
+/// This represents a common set of key agreement errors. Implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The hardware engine is busy.
+    Busy,
+
+    /// Generating the private key failed, e.g. due to an entropy failure.
+    KeyGenError,
+
+    /// The peer public key is a low-order point, yielding an all-zero shared secret.
+    LowOrderPoint,
+
+    /// Any other error.
+    Other,
+}
+
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic error kind
+    ///
+    /// By using this method, errors freely defined by HAL implementations
+    /// can be converted to a set of generic errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Version of the trait contract defined by this module.
+pub const TRAIT_CONTRACT_VERSION: ContractVersion = ContractVersion::new(1, 0);
+
+pub trait ErrorType {
+    /// Error type.
+    type Error: Error;
+}
+
+/// Reports the version of this module's trait contract a provider was
+/// built against.
+pub trait Contract {
+    /// Version of the trait contract this provider was built against.
+    fn contract_version(&self) -> ContractVersion {
+        TRAIT_CONTRACT_VERSION
+    }
+}
+
+/// X25519 public key: a Montgomery u-coordinate, little endian (RFC 7748).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PublicKey(pub [u8; KEY_SIZE]);
+
+/// Shared secret produced by X25519, cleared when dropped.
+pub struct SharedSecret([u8; KEY_SIZE]);
+
+impl SharedSecret {
+    /// Wraps the output of the X25519 function.
+    pub fn from_bytes(bytes: [u8; KEY_SIZE]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for SharedSecret {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl ConstantTimeEq for SharedSecret {
+    fn ct_eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl Zeroize for SharedSecret {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Trait for X25519 (RFC 7748) key agreement.
+///
+/// X25519 works on the Montgomery form of Curve25519 with fixed 32-byte
+/// encodings, so it has its own key types rather than going through the
+/// Weierstrass curve abstraction used by ECDSA.
+pub trait X25519KeyExchange: ErrorType {
+    /// Private key, possibly a handle to key material held by the engine.
+    type PrivateKey: Zeroize;
+
+    /// Generates an ephemeral key pair.
+    ///
+    /// # Returns
+    /// A result containing the private key and the public key to send to the peer, or an error.
+    fn generate_ephemeral(&mut self) -> Result<(Self::PrivateKey, PublicKey), Self::Error>;
+
+    /// Computes the shared secret with a peer.
+    ///
+    /// The private key is consumed, since ephemeral keys must not be reused.
+    ///
+    /// # Parameters
+    /// - `private_key`: Our private key.
+    /// - `peer`: The peer's public key.
+    ///
+    /// # Returns
+    /// A result containing the shared secret, or `ErrorKind::LowOrderPoint` if it is all zero.
+    fn compute_shared_secret(
+        &mut self,
+        private_key: Self::PrivateKey,
+        peer: &PublicKey,
+    ) -> Result<SharedSecret, Self::Error>;
+}
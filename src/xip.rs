@@ -0,0 +1,212 @@
+use crate::block_device::{
+    BlockDevice, Error, ErrorKind, ErrorType, ProgramCapabilities, ReadOnlyBlockDevice,
+};
+use crate::budget::ResourceBudget;
+use crate::units::ByteOffset;
+
+/// How an [`XipInterlock`] made a flash range safe to modify.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum XipMode {
+    /// The CPU does not execute from the range.
+    NotExecuting,
+    /// Execution was redirected to an alternate copy of the range.
+    Remapped,
+    /// The code executing from the range was copied to RAM and runs from there.
+    Shadowed,
+}
+
+/// Interlock between execute-in-place and modification of the same flash.
+///
+/// Erasing or programming a flash the CPU is executing from stalls or
+/// corrupts instruction fetches. Before such an operation the interlock is
+/// acquired; it either reports that the range is not executed from, or moves
+/// execution away using the remap or RAM shadow hooks. If neither hook is
+/// available the operation must be refused.
+pub trait XipInterlock {
+    /// Returns `true` if the CPU may fetch instructions from the range.
+    fn executing_from(&self, addr: ByteOffset, len: usize) -> bool;
+
+    /// Redirects execution to an alternate copy of the range.
+    ///
+    /// Returns `false` if the platform cannot remap the range.
+    fn remap(&mut self, _addr: ByteOffset, _len: usize) -> bool {
+        false
+    }
+
+    /// Copies the code executing from the range to RAM and runs it from there.
+    ///
+    /// Returns `false` if the platform cannot shadow the range.
+    fn shadow_to_ram(&mut self, _addr: ByteOffset, _len: usize) -> bool {
+        false
+    }
+
+    /// Undoes the remap or RAM shadow established by `acquire`.
+    fn restore(&mut self, mode: XipMode);
+
+    /// Makes the range safe to modify.
+    ///
+    /// # Returns
+    ///
+    /// The mode to pass back to `restore`, or `None` if the range is executed
+    /// from and could be neither remapped nor shadowed.
+    fn acquire(&mut self, addr: ByteOffset, len: usize) -> Option<XipMode> {
+        if !self.executing_from(addr, len) {
+            Some(XipMode::NotExecuting)
+        } else if self.remap(addr, len) {
+            Some(XipMode::Remapped)
+        } else if self.shadow_to_ram(addr, len) {
+            Some(XipMode::Shadowed)
+        } else {
+            None
+        }
+    }
+}
+
+/// Error returned by [`Interlocked`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterlockedError<E> {
+    /// Underlying block device error
+    Device(E),
+    /// The target range is executed from and could not be released
+    XipConflict,
+}
+
+impl<E: Error> Error for InterlockedError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            InterlockedError::Device(e) => e.kind(),
+            InterlockedError::XipConflict => ErrorKind::XipConflict,
+        }
+    }
+}
+
+/// [`BlockDevice`] adapter which acquires an [`XipInterlock`] around every
+/// erase and program, refusing operations that would disturb execution.
+pub struct Interlocked<D, I> {
+    device: D,
+    interlock: I,
+}
+
+impl<D, I> Interlocked<D, I>
+where
+    D: BlockDevice,
+    I: XipInterlock,
+{
+    pub fn new(device: D, interlock: I) -> Self {
+        Self { device, interlock }
+    }
+
+    /// Releases the device and the interlock.
+    pub fn release(self) -> (D, I) {
+        (self.device, self.interlock)
+    }
+
+    fn guarded<T>(
+        &mut self,
+        addr: ByteOffset,
+        len: usize,
+        op: impl FnOnce(&mut D) -> Result<T, D::Error>,
+    ) -> Result<T, InterlockedError<D::Error>> {
+        let mode = self
+            .interlock
+            .acquire(addr, len)
+            .ok_or(InterlockedError::XipConflict)?;
+        let result = op(&mut self.device);
+        if mode != XipMode::NotExecuting {
+            self.interlock.restore(mode);
+        }
+        result.map_err(InterlockedError::Device)
+    }
+}
+
+impl<D, I> ErrorType for Interlocked<D, I>
+where
+    D: BlockDevice,
+{
+    type Error = InterlockedError<D::Error>;
+}
+
+impl<D: ResourceBudget, I> ResourceBudget for Interlocked<D, I> {
+    const MAX_CONTEXT_SIZE: usize = D::MAX_CONTEXT_SIZE;
+    const MAX_CONCURRENT_OPS: usize = D::MAX_CONCURRENT_OPS;
+    const SCRATCH_BYTES_REQUIRED: usize = D::SCRATCH_BYTES_REQUIRED;
+}
+
+impl<D, I> ReadOnlyBlockDevice for Interlocked<D, I>
+where
+    D: BlockDevice,
+    I: XipInterlock,
+{
+    fn read_size(&self) -> usize {
+        self.device.read_size()
+    }
+
+    fn read(&mut self, block_addr: ByteOffset, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.device
+            .read(block_addr, data)
+            .map_err(InterlockedError::Device)
+    }
+
+    fn capacity(&self) -> usize {
+        self.device.capacity()
+    }
+}
+
+impl<D, I> BlockDevice for Interlocked<D, I>
+where
+    D: BlockDevice,
+    I: XipInterlock,
+{
+    fn erase_size(&self) -> usize {
+        self.device.erase_size()
+    }
+
+    fn erase(&mut self, block_addr: ByteOffset, size_in_bytes: usize) -> Result<(), Self::Error> {
+        self.guarded(block_addr, size_in_bytes, |d| {
+            d.erase(block_addr, size_in_bytes)
+        })
+    }
+
+    fn program_size(&self) -> usize {
+        self.device.program_size()
+    }
+
+    fn program(&mut self, block_addr: ByteOffset, data: &[u8]) -> Result<(), Self::Error> {
+        self.guarded(block_addr, data.len(), |d| d.program(block_addr, data))
+    }
+
+    fn program_capabilities(&self) -> ProgramCapabilities {
+        self.device.program_capabilities()
+    }
+}
+
+/// Device contents exposed through a memory window, e.g. XIP SPI NOR or an
+/// eMMC boot partition, so loaders can read them in place.
+///
+/// The returned slice borrows the device, so it cannot outlive the mapping
+/// or be used across a reprogramming of the device.
+pub trait MemoryMappedDevice: ErrorType {
+    /// Largest range that can be mapped at once, in bytes.
+    fn max_map_len(&self) -> usize;
+
+    /// Maps `len` bytes starting at `addr` into the memory window.
+    ///
+    /// # Parameters
+    /// - `addr`: Device offset of the range.
+    /// - `len`: Length of the range, at most `max_map_len()`.
+    ///
+    /// # Returns
+    /// The range as it appears in the window, or an error.
+    fn map_region(&mut self, addr: ByteOffset, len: usize) -> Result<&[u8], Self::Error>;
+
+    /// Releases the current mapping.
+    fn unmap(&mut self) -> Result<(), Self::Error>;
+
+    /// Discards cached window contents for the range, so reads observe data
+    /// written to the device since it was last mapped.
+    ///
+    /// The default does nothing, for windows that are not cached.
+    fn invalidate_cache(&mut self, _addr: ByteOffset, _len: usize) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Tests of the `ToBytes`/`FromBytes` derive macros.
+
+#![cfg(feature = "derive")]
+
+use peripheral_traits::common::{BytesError, Endian, FixedLen, FromBytes, ToBytes};
+
+#[derive(Debug, PartialEq, ToBytes, FromBytes)]
+struct Record {
+    id: u32,
+    len: u16,
+    tag: [u8; 3],
+}
+
+#[derive(Debug, PartialEq, ToBytes, FromBytes)]
+#[bytes(endian = "big")]
+struct Mixed {
+    magic: u32,
+    #[bytes(endian = "little")]
+    version: u16,
+    flags: u16,
+}
+
+/// Fields named like the generated parameters and locals.
+#[derive(Debug, PartialEq, ToBytes, FromBytes)]
+struct Shadowing {
+    bytes: [u8; 2],
+    endian: u16,
+    at: u8,
+    dest: u8,
+}
+
+#[derive(Debug, PartialEq, ToBytes, FromBytes)]
+struct Nested {
+    header: Mixed,
+    record: Record,
+}
+
+fn round_trip<T, const N: usize>(value: &T, endian: Endian) -> [u8; N]
+where
+    T: ToBytes + FromBytes + FixedLen + PartialEq + core::fmt::Debug,
+{
+    assert_eq!(T::BYTE_LEN, N);
+    assert_eq!(value.byte_len(), N);
+    let mut buf = [0u8; N];
+    assert_eq!(value.to_bytes(&mut buf, endian), Ok(N));
+    assert_eq!(T::from_bytes(&buf, endian).as_ref(), Ok(value));
+    buf
+}
+
+#[test]
+fn round_trip_follows_the_requested_byte_order() {
+    let record = Record {
+        id: 0x0102_0304,
+        len: 0x0506,
+        tag: *b"abc",
+    };
+    assert_eq!(
+        round_trip::<_, 9>(&record, Endian::Little),
+        *b"\x04\x03\x02\x01\x06\x05abc"
+    );
+    assert_eq!(
+        round_trip::<_, 9>(&record, Endian::Big),
+        *b"\x01\x02\x03\x04\x05\x06abc"
+    );
+}
+
+#[test]
+fn attributes_fix_the_byte_order() {
+    let mixed = Mixed {
+        magic: 0x1122_3344,
+        version: 0x0102,
+        flags: 0x0304,
+    };
+    let expected = *b"\x11\x22\x33\x44\x02\x01\x03\x04";
+    assert_eq!(round_trip::<_, 8>(&mixed, Endian::Little), expected);
+    assert_eq!(round_trip::<_, 8>(&mixed, Endian::Big), expected);
+}
+
+#[test]
+fn fields_may_shadow_generated_names() {
+    let value = Shadowing {
+        bytes: [0xAA, 0xBB],
+        endian: 0x0102,
+        at: 3,
+        dest: 4,
+    };
+    assert_eq!(
+        round_trip::<_, 6>(&value, Endian::Little),
+        [0xAA, 0xBB, 0x02, 0x01, 3, 4]
+    );
+}
+
+#[test]
+fn derived_structs_nest() {
+    let nested = Nested {
+        header: Mixed {
+            magic: 1,
+            version: 2,
+            flags: 3,
+        },
+        record: Record {
+            id: 4,
+            len: 5,
+            tag: *b"xyz",
+        },
+    };
+    round_trip::<_, 17>(&nested, Endian::Little);
+}
+
+#[test]
+fn wrong_lengths_are_rejected() {
+    let record = Record {
+        id: 1,
+        len: 2,
+        tag: *b"abc",
+    };
+    assert_eq!(
+        record.to_bytes(&mut [0; 8], Endian::Little),
+        Err(BytesError::BufferTooSmall)
+    );
+    assert_eq!(
+        Record::from_bytes(&[0; 8], Endian::Little),
+        Err(BytesError::InvalidLength)
+    );
+    assert_eq!(
+        Record::from_bytes(&[0; 10], Endian::Little),
+        Err(BytesError::InvalidLength)
+    );
+}